@@ -4,7 +4,7 @@ import { useEffect, useState, useRef, Suspense } from "react";
 import { useSearchParams } from "next/navigation";
 import { EntryEditor } from "@/components/entry-editor";
 import type { EntryData } from "@/lib/tauri";
-import { getEntries } from "@/lib/tauri";
+import { getEntries, setActiveDatabaseId } from "@/lib/tauri";
 import { getCurrentWindow } from "@tauri-apps/api/window";
 import { ask } from "@tauri-apps/plugin-dialog";
 
@@ -12,6 +12,7 @@ function EntryContent() {
   const searchParams = useSearchParams();
   const uuid = searchParams.get('uuid');
   const groupUuid = searchParams.get('groupUuid');
+  const databaseId = searchParams.get('databaseId');
   const [entry, setEntry] = useState<EntryData | null>(null);
   const [loading, setLoading] = useState(true);
   const [hasUnsavedChanges, setHasUnsavedChanges] = useState(false);
@@ -26,12 +27,13 @@ function EntryContent() {
   useEffect(() => {
     const loadEntry = async () => {
       try {
-        if (!groupUuid || !uuid) {
+        if (!groupUuid || !uuid || !databaseId) {
           console.error('Missing UUID parameters');
           setLoading(false);
           return;
         }
 
+        setActiveDatabaseId(databaseId);
         const entries = await getEntries(groupUuid);
         const foundEntry = entries.find((e) => e.uuid === uuid);
         
@@ -48,7 +50,7 @@ function EntryContent() {
     if (uuid && groupUuid) {
       loadEntry();
     }
-  }, [uuid, groupUuid]);
+  }, [uuid, groupUuid, databaseId]);
 
   // Handle window close event with unsaved changes check
   useEffect(() => {
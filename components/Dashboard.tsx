@@ -66,11 +66,14 @@ export function Dashboard({ refreshTrigger, databasePath, isDirty }: DashboardPr
     }
   };
 
+  // `bits` is log2 of the estimated guesses. The steps are the backend's score
+  // boundaries (10^3, 10^6, 10^8 and 10^10 guesses), so "Strong" starts where a
+  // password stops counting as weak.
   const getStrengthLevel = (bits: number): { label: string; color: string } => {
-    if (bits < 40) return { label: "Weak", color: "text-red-500" };
-    if (bits < 64) return { label: "Fair", color: "text-orange-500" };
-    if (bits < 80) return { label: "Good", color: "text-yellow-500" };
-    if (bits < 112) return { label: "Strong", color: "text-blue-500" };
+    if (bits < Math.log2(1e3)) return { label: "Weak", color: "text-red-500" };
+    if (bits < Math.log2(1e6)) return { label: "Fair", color: "text-orange-500" };
+    if (bits < Math.log2(1e8)) return { label: "Good", color: "text-yellow-500" };
+    if (bits < Math.log2(1e10)) return { label: "Strong", color: "text-blue-500" };
     return { label: "Excellent", color: "text-green-500" };
   };
 
@@ -174,7 +177,7 @@ export function Dashboard({ refreshTrigger, databasePath, isDirty }: DashboardPr
                   {strengthInfo.label}
                 </div>
                 <p className="text-xs text-muted-foreground mt-1">
-                  {stats ? Math.round(stats.average_password_strength) : "..."} bits entropy
+                  {stats ? Math.round(stats.average_password_strength) : "..."} bits (log2 guesses)
                 </p>
               </CardContent>
             </Card>
@@ -294,7 +297,7 @@ export function Dashboard({ refreshTrigger, databasePath, isDirty }: DashboardPr
                     <div>
                       <p className="font-medium">Update weak passwords</p>
                       <p className="text-muted-foreground text-xs">
-                        {stats.weak_passwords} password{stats.weak_passwords !== 1 ? 's' : ''} with less than 40 bits of entropy
+                        {stats.weak_passwords} password{stats.weak_passwords !== 1 ? 's' : ''} that are easy to guess
                       </p>
                     </div>
                   </div>
@@ -6,11 +6,10 @@ import { Button } from "@/components/ui/button";
 import { Input } from "@/components/ui/input";
 import { Label } from "@/components/ui/label";
 import Image from "next/image";
-import { openDatabase } from "@/lib/tauri";
+import { openDatabase, getKdfInfo, upgradeKdfParameters } from "@/lib/tauri";
 import { useToast } from "@/components/ui/use-toast";
 import { KdfWarningDialog } from "@/components/KdfWarningDialog";
 import { CustomTitleBar } from "@/components/CustomTitleBar";
-import { invoke } from "@tauri-apps/api/core";
 
 interface QuickUnlockScreenProps {
   lastDatabasePath: string;
@@ -41,7 +40,14 @@ export function QuickUnlockScreen({
 
     setLoading(true);
     try {
-      const [rootGroup, dbPath] = await openDatabase(lastDatabasePath, password);
+      const [, rootGroup, dbPath, , replacedLock] = await openDatabase(lastDatabasePath, password);
+      if (replacedLock) {
+        // Opening took over a lock that looked abandoned; whoever left it may still be editing
+        toast({
+          title: "Stale Lock Replaced",
+          description: `This database was locked by ${replacedLock.owner} on ${replacedLock.host} since ${replacedLock.timestamp} UTC. That lock looked abandoned and has been taken over.`,
+        });
+      }
       
       // Check if KDF warning was dismissed for this database
       const dismissedDbs = JSON.parse(localStorage.getItem("kdf_warning_dismissed_dbs") || "[]");
@@ -50,13 +56,7 @@ export function QuickUnlockScreen({
       // Check KDF parameters
       if (!isDismissedForThisDb) {
         try {
-          const kdfInfo = await invoke<{
-            kdf_type: string;
-            is_weak: boolean;
-            iterations?: number;
-            memory?: number;
-            parallelism?: number;
-          }>("get_kdf_info");
+          const kdfInfo = await getKdfInfo();
           
           if (kdfInfo.is_weak) {
             setKdfType(kdfInfo.kdf_type);
@@ -88,7 +88,7 @@ export function QuickUnlockScreen({
 
   const handleKdfUpgrade = async () => {
     try {
-      await invoke("upgrade_kdf_parameters");
+      await upgradeKdfParameters();
       toast({
         title: "Success",
         description: "Key transformation settings upgraded successfully",
@@ -6,14 +6,13 @@ import { Button } from "@/components/ui/button";
 import { Input } from "@/components/ui/input";
 import { Label } from "@/components/ui/label";
 import { FolderOpen, Plus } from "lucide-react";
-import { openDatabase } from "@/lib/tauri";
+import { openDatabase, getKdfInfo, upgradeKdfParameters } from "@/lib/tauri";
 import { useToast } from "@/components/ui/use-toast";
 import { open } from "@tauri-apps/plugin-dialog";
 import { CreateDatabaseDialog } from "@/components/CreateDatabaseDialog";
 import { KdfWarningDialog } from "@/components/KdfWarningDialog";
 import { CustomTitleBar } from "@/components/CustomTitleBar";
 import { saveLastDatabasePath } from "@/lib/storage";
-import { invoke } from "@tauri-apps/api/core";
 import Image from "next/image";
 
 interface UnlockScreenProps {
@@ -73,7 +72,14 @@ export function UnlockScreen({ onUnlock, initialFilePath }: UnlockScreenProps) {
 
     setLoading(true);
     try {
-      const [rootGroup, dbPath] = await openDatabase(filePath, password);
+      const [, rootGroup, dbPath, , replacedLock] = await openDatabase(filePath, password);
+      if (replacedLock) {
+        // Opening took over a lock that looked abandoned; whoever left it may still be editing
+        toast({
+          title: "Stale Lock Replaced",
+          description: `This database was locked by ${replacedLock.owner} on ${replacedLock.host} since ${replacedLock.timestamp} UTC. That lock looked abandoned and has been taken over.`,
+        });
+      }
       saveLastDatabasePath(filePath);
       
       // Check if KDF warning was dismissed for this database
@@ -83,13 +89,7 @@ export function UnlockScreen({ onUnlock, initialFilePath }: UnlockScreenProps) {
       // Check KDF parameters
       if (!isDismissedForThisDb) {
         try {
-          const kdfInfo = await invoke<{
-            kdf_type: string;
-            is_weak: boolean;
-            iterations?: number;
-            memory?: number;
-            parallelism?: number;
-          }>("get_kdf_info");
+          const kdfInfo = await getKdfInfo();
           
           if (kdfInfo.is_weak) {
             setKdfType(kdfInfo.kdf_type);
@@ -121,7 +121,7 @@ export function UnlockScreen({ onUnlock, initialFilePath }: UnlockScreenProps) {
 
   const handleKdfUpgrade = async () => {
     try {
-      await invoke("upgrade_kdf_parameters");
+      await upgradeKdfParameters();
       toast({
         title: "Success",
         description: "Key transformation settings upgraded successfully",
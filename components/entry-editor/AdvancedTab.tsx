@@ -1,6 +1,6 @@
 "use client";
 
-import { useState } from "react";
+import { useEffect, useState } from "react";
 import { Button } from "@/components/ui/button";
 import { Input } from "@/components/ui/input";
 import { Label } from "@/components/ui/label";
@@ -26,6 +26,14 @@ interface AdvancedTabProps {
 export function AdvancedTab({ formData, setFormData, setHasChanges }: AdvancedTabProps) {
   const [selectedCustomField, setSelectedCustomField] = useState<number | null>(null);
   const [editingCustomField, setEditingCustomField] = useState<CustomField | null>(null);
+  // Kept as typed, so a trailing separator isn't swallowed while editing
+  const [tagsText, setTagsText] = useState(() => formData.tags.join(", "));
+
+  useEffect(() => {
+    setTagsText(formData.tags.join(", "));
+    // Only reset when a different entry is loaded
+    // eslint-disable-next-line react-hooks/exhaustive-deps
+  }, [formData.uuid]);
 
   const handleAddField = () => {
     const newField: CustomField = { name: "", value: "", protected: false };
@@ -64,7 +72,9 @@ export function AdvancedTab({ formData, setFormData, setHasChanges }: AdvancedTa
   };
 
   const handleTagsChange = (value: string) => {
-    setFormData(prev => ({ ...prev, tags: value }));
+    setTagsText(value);
+    const tags = value.split(/[;,]/).map(tag => tag.trim()).filter(tag => tag.length > 0);
+    setFormData(prev => ({ ...prev, tags }));
     setHasChanges(true);
   };
 
@@ -188,7 +198,7 @@ export function AdvancedTab({ formData, setFormData, setHasChanges }: AdvancedTa
           </div>
           <div className="p-3">
             <Input
-              value={formData.tags}
+              value={tagsText}
               onChange={(e) => handleTagsChange(e.target.value)}
               placeholder="Comma-separated tags"
             />
@@ -4,7 +4,7 @@ import { useState, useEffect } from "react";
 import { Button } from "@/components/ui/button";
 import { Tabs, TabsContent, TabsList, TabsTrigger } from "@/components/ui/tabs";
 import { Save } from "lucide-react";
-import { updateEntry, generatePassword } from "@/lib/tauri";
+import { updateEntry, generatePassword, recordEntryAccess } from "@/lib/tauri";
 import { useToast } from "@/components/ui/use-toast";
 import { writeText } from "@tauri-apps/plugin-clipboard-manager";
 import { emit } from "@tauri-apps/api/event";
@@ -126,6 +126,7 @@ export function EntryEditor({ entry, onClose, onRefresh, onHasChangesChange }: E
   const handleCopy = async (text: string, label: string, field: 'username' | 'password') => {
     try {
       await writeText(text);
+      recordEntryAccess(formData.uuid, field === 'username' ? "copy_username" : "copy_password").catch(() => {});
       
       // Set copied state for visual feedback
       if (field === 'username') {
@@ -15,6 +15,7 @@ import { useDraggable } from "@dnd-kit/core";
 import { useToast } from "@/components/ui/use-toast";
 import type { EntryData } from "@/lib/tauri";
 import type { ColumnConfig } from "./types";
+import type { EntryAccess } from "./hooks/useClipboard";
 
 interface EntryListItemProps {
   entry: EntryData;
@@ -26,8 +27,8 @@ interface EntryListItemProps {
   onSelect: () => void;
   onToggleCheck: () => void;
   onContextMenuChange: (open: boolean) => void;
-  onCopyField: (text: string, fieldName: string) => void;
-  onOpenUrl: (url: string) => void;
+  onCopyField: (text: string, fieldName: string, access?: EntryAccess) => void;
+  onOpenUrl: (url: string, entryUuid: string) => void;
   onDuplicate: () => void;
   onDelete: () => void;
   onRefresh: () => void;
@@ -139,7 +140,7 @@ export function EntryListItem({
                         className="truncate text-sm text-muted-foreground cursor-pointer px-1"
                         onDoubleClick={(e) => {
                           e.stopPropagation();
-                          onCopyField(entry.username, "Username");
+                          onCopyField(entry.username, "Username", { entryUuid: entry.uuid, action: "copy_username" });
                         }}
                         title="Double-click to copy"
                       >
@@ -151,7 +152,7 @@ export function EntryListItem({
                         className="truncate text-sm text-muted-foreground font-mono cursor-pointer px-1"
                         onDoubleClick={(e) => {
                           e.stopPropagation();
-                          onCopyField(entry.password, "Password");
+                          onCopyField(entry.password, "Password", { entryUuid: entry.uuid, action: "copy_password" });
                         }}
                         title="Double-click to copy"
                       >
@@ -164,7 +165,7 @@ export function EntryListItem({
                           <button
                             onClick={(e) => {
                               e.stopPropagation();
-                              onOpenUrl(entry.url);
+                              onOpenUrl(entry.url, entry.uuid);
                             }}
                             className="flex items-center gap-1 text-sm text-blue-600 dark:text-blue-400 hover:underline truncate group"
                             title={entry.url}
@@ -226,14 +227,14 @@ export function EntryListItem({
         </ContextMenuItem>
         <ContextMenuSeparator />
         <ContextMenuItem 
-          onClick={() => onCopyField(entry.username, "Username")}
+          onClick={() => onCopyField(entry.username, "Username", { entryUuid: entry.uuid, action: "copy_username" })}
           disabled={!entry.username}
         >
           <User className="mr-2 h-4 w-4" />
           Copy Username
         </ContextMenuItem>
         <ContextMenuItem 
-          onClick={() => onCopyField(entry.password, "Password")}
+          onClick={() => onCopyField(entry.password, "Password", { entryUuid: entry.uuid, action: "copy_password" })}
           disabled={!entry.password}
         >
           <Key className="mr-2 h-4 w-4" />
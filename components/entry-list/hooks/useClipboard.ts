@@ -3,12 +3,20 @@
 import { useState } from "react";
 import { writeText } from "@tauri-apps/plugin-clipboard-manager";
 import { useToast } from "@/components/ui/use-toast";
+import { recordEntryAccess } from "@/lib/tauri";
+import type { AccessAction } from "@/lib/tauri";
+
+// Which entry a copied value belongs to, so the copy counts as a use of it
+export interface EntryAccess {
+  entryUuid: string;
+  action: AccessAction;
+}
 
 export function useClipboard() {
   const [clearTimeoutId, setClearTimeoutId] = useState<NodeJS.Timeout | null>(null);
   const { toast } = useToast();
 
-  const handleCopyField = async (text: string, fieldName: string) => {
+  const handleCopyField = async (text: string, fieldName: string, access?: EntryAccess) => {
     if (!text) {
       toast({
         title: "Nothing to copy",
@@ -21,6 +29,11 @@ export function useClipboard() {
     try {
       await writeText(text);
 
+      if (access) {
+        // Usage statistics are best-effort; a failure must not look like a failed copy
+        recordEntryAccess(access.entryUuid, access.action).catch(() => {});
+      }
+
       // Clear any existing timeout
       if (clearTimeoutId) {
         clearTimeout(clearTimeoutId);
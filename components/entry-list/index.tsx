@@ -9,7 +9,7 @@ import {
   ContextMenuTrigger,
 } from "@/components/ui/context-menu";
 import { Plus } from "lucide-react";
-import { getEntries, createEntry, deleteEntry } from "@/lib/tauri";
+import { getEntries, createEntry, deleteEntry, deleteEntries, recordEntryAccess, getDatabaseMetadata } from "@/lib/tauri";
 import { useToast } from "@/components/ui/use-toast";
 import type { EntryData } from "@/lib/tauri";
 import { ask } from "@tauri-apps/plugin-dialog";
@@ -177,15 +177,17 @@ export function EntryList({
     try {
       // Generate UUID for the new entry
       const entryUuid = crypto.randomUUID();
+      // Start from the database's default username, like KeePass; the editor can clear it
+      const { default_username } = await getDatabaseMetadata();
       
       const newEntry: EntryData = {
         uuid: entryUuid,
         title: newEntryTitle,
-        username: "",
+        username: default_username,
         password: "",
         url: "",
         notes: "",
-        tags: "",
+        tags: [],
         group_uuid: targetGroupUuid,
         icon_id: newEntryIconId,
         is_favorite: isFavoritesView, // Auto-favorite if created in Favorites view
@@ -294,10 +296,12 @@ export function EntryList({
     if (!shouldDelete) return;
 
     try {
-      // Delete all selected entries
-      await Promise.all(
-        Array.from(selectedEntries).map(uuid => deleteEntry(uuid))
-      );
+      // Delete all selected entries in a single all-or-nothing batch
+      const result = await deleteEntries(Array.from(selectedEntries));
+      if (!result.applied) {
+        const failed = result.results.find(r => r.error && !r.error.startsWith("Not applied"));
+        throw failed?.error || "Failed to delete entries";
+      }
       
       toast({
         title: "Success",
@@ -316,13 +320,14 @@ export function EntryList({
     }
   };
 
-  const handleOpenUrl = async (url: string) => {
+  const handleOpenUrl = async (url: string, entryUuid: string) => {
     if (!url) return;
     
     try {
       // Add https:// if no protocol specified
       const fullUrl = url.match(/^https?:\/\//) ? url : `https://${url}`;
       await open(fullUrl);
+      recordEntryAccess(entryUuid, "open_url").catch(() => {});
     } catch (error: any) {
       toast({
         title: "Error",
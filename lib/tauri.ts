@@ -1,4 +1,24 @@
 import { invoke } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+
+// Several vaults can be open at once; each window works on one of them.
+// The main window sets this on create/open, entry windows from their URL.
+let activeDatabaseId: string | null = null;
+
+export function setActiveDatabaseId(id: string | null) {
+  activeDatabaseId = id;
+}
+
+export function getActiveDatabaseId(): string | null {
+  return activeDatabaseId;
+}
+
+function databaseId(): string {
+  if (!activeDatabaseId) {
+    throw new Error("No database loaded");
+  }
+  return activeDatabaseId;
+}
 
 export interface CustomField {
   name: string;
@@ -22,7 +42,7 @@ export interface EntryData {
   password: string;
   url: string;
   notes: string;
-  tags: string;
+  tags: string[];
   group_uuid: string;
   icon_id?: number;
   is_favorite: boolean;
@@ -36,12 +56,48 @@ export interface EntryData {
   history: HistoryEntry[];
 }
 
+export type AccessAction = "copy_password" | "copy_username" | "open_url" | "auto_type";
+
 export interface GroupData {
   uuid: string;
   name: string;
   parent_uuid: string | null;
   children: GroupData[];
   icon_id?: number;
+  // The group's own settings; null inherits from the parent
+  enable_searching: boolean | null;
+  enable_auto_type: boolean | null;
+  is_recycle_bin: boolean;
+}
+
+export type KdfAlgorithm = "aes" | "argon2d" | "argon2id";
+
+export type KdfSettings =
+  | { kdf_type: "aes"; rounds: number }
+  | { kdf_type: "argon2d"; iterations: number; memory: number; parallelism: number }
+  | { kdf_type: "argon2id"; iterations: number; memory: number; parallelism: number };
+
+export interface DatabaseSettings {
+  outer_cipher: "aes256" | "twofish" | "chacha20";
+  inner_cipher: "plain" | "salsa20" | "chacha20";
+  compression: "none" | "gzip";
+  kdbx_version: string;
+}
+
+export interface DatabaseMetadata {
+  name: string;
+  description: string;
+  default_username: string;
+  color: string | null;
+  maintenance_history_days: number | null;
+}
+
+export interface MasterKeyStatus {
+  last_changed: string | null;
+  recommend_after_days: number | null;
+  force_after_days: number | null;
+  change_recommended: boolean;
+  change_forced: boolean;
 }
 
 export interface DashboardStats {
@@ -55,6 +111,88 @@ export interface DashboardStats {
   average_password_strength: number;
 }
 
+export type ChangeKind =
+  | "entry_created"
+  | "entry_updated"
+  | "entry_deleted"
+  | "entry_moved"
+  | "entry_accessed"
+  | "group_created"
+  | "group_updated"
+  | "group_deleted"
+  | "group_moved"
+  | "settings_changed"
+  | "saved_searches_changed"
+  | "merged"
+  | "undone"
+  | "redone"
+  | "saved";
+
+export interface DatabaseChanged {
+  database_id: string;
+  kind: ChangeKind;
+  uuids: string[];
+  revision: number;
+  dirty: boolean;
+}
+
+export interface DatabaseStatus {
+  revision: number;
+  dirty: boolean;
+  read_only: boolean;
+}
+
+export type AutosaveMode = "off" | "after_change" | "debounced" | "on_lock_close";
+
+export interface AutosaveSettings {
+  mode: AutosaveMode;
+  debounce_seconds: number;
+}
+
+export interface UndoState {
+  can_undo: boolean;
+  can_redo: boolean;
+  undo_label: string | null;
+  redo_label: string | null;
+  max_depth: number;
+  keep_across_saves: boolean;
+}
+
+// A pattern-aware estimate: `score` runs from 0 (too guessable) to 4 (very unguessable)
+export interface PasswordQuality {
+  guesses: number;
+  entropy_bits: number;
+  score: number;
+  warning: string | null;
+  suggestions: string[];
+}
+
+export interface BulkItemResult {
+  uuid: string;
+  success: boolean;
+  error: string | null;
+}
+
+export interface BulkOperationResult {
+  applied: boolean;
+  results: BulkItemResult[];
+}
+
+export interface LockInfo {
+  id: string;
+  owner: string;
+  host: string;
+  pid: number;
+  timestamp: string;
+}
+
+export interface LockStatus {
+  locked: boolean;
+  stale: boolean;
+  owned_by_us: boolean;
+  info: LockInfo | null;
+}
+
 export interface BreachedEntry {
   uuid: string;
   title: string;
@@ -62,69 +200,335 @@ export interface BreachedEntry {
   breach_count: number;
 }
 
-export async function createDatabase(path: string, password: string): Promise<GroupData> {
-  return invoke("create_database", { path, password });
+export interface KdfInfo {
+  kdf_type: string;
+  is_weak: boolean;
+  iterations?: number;
+  memory?: number;
+  parallelism?: number;
+}
+
+export interface OpenDatabaseInfo {
+  id: string;
+  path: string;
+  name: string;
+  dirty: boolean;
+  read_only: boolean;
 }
 
-export async function openDatabase(path: string, password: string): Promise<[GroupData, string]> {
-  return invoke("open_database", { path, password });
+// Byte range of a hit in a KDBX field ("Title", "UserName", "URL", "Notes", "Tags",
+// "Password", or a custom field's or attachment's name). `in_name` means the range is
+// in the name itself; for "Tags" it is within `tags[tag_index]`. `history_index` points
+// into `history` for hits in older versions.
+export interface SearchHighlight {
+  field: string;
+  in_name: boolean;
+  start: number;
+  end: number;
+  tag_index: number | null;
+  history_index: number | null;
+}
+
+// Search results come back best match first
+export interface SearchResult extends EntryData {
+  score: number;
+  highlights: SearchHighlight[];
+}
+
+export interface DatabaseSearchResult extends SearchResult {
+  database_id: string;
+  database_name: string;
+}
+
+// All off is a case-insensitive substring search over unprotected fields
+export interface SearchOptions {
+  regex?: boolean;
+  case_sensitive?: boolean;
+  include_protected?: boolean;
+  include_history?: boolean;
+}
+
+export interface TagCount {
+  name: string;
+  count: number;
+}
+
+export interface SavedSearch {
+  uuid: string;
+  name: string;
+  query: string;
+  options: SearchOptions;
+}
+
+export async function listOpenDatabases(): Promise<OpenDatabaseInfo[]> {
+  return await invoke<OpenDatabaseInfo[]>("list_open_databases");
+}
+
+export async function createDatabase(path: string, password: string): Promise<[string, GroupData]> {
+  const result = await invoke<[string, GroupData]>("create_database", { path, password });
+  setActiveDatabaseId(result[0]);
+  return result;
+}
+
+export async function openDatabase(
+  path: string,
+  password: string,
+  readOnly = false
+): Promise<[string, GroupData, string, MasterKeyStatus, LockInfo | null]> {
+  const result = await invoke<[string, GroupData, string, MasterKeyStatus, LockInfo | null]>("open_database", {
+    path,
+    password,
+    readOnly,
+  });
+  setActiveDatabaseId(result[0]);
+  return result;
+}
+
+export async function getDatabaseLockStatus(path: string): Promise<LockStatus> {
+  return await invoke<LockStatus>("get_database_lock_status", { path });
+}
+
+export async function breakDatabaseLock(path: string): Promise<void> {
+  return await invoke<void>("break_database_lock", { path });
+}
+
+export async function setAutoMergeExternalChanges(enabled: boolean): Promise<void> {
+  return await invoke<void>("set_auto_merge_external_changes", { enabled });
+}
+
+export async function onDatabaseChangedExternally(
+  handler: (event: { database_id: string; path: string; merged: boolean }) => void
+): Promise<UnlistenFn> {
+  return await listen<{ database_id: string; path: string; merged: boolean }>("database-changed-externally", (event) =>
+    handler(event.payload)
+  );
 }
 
 export async function saveDatabase(): Promise<void> {
-  return await invoke<void>("save_database");
+  return await invoke<void>("save_database", { databaseId: databaseId() });
+}
+
+export async function saveDatabaseAs(
+  path: string,
+  newPassword?: string,
+  kdf?: KdfSettings
+): Promise<void> {
+  return await invoke<void>("save_database_as", { databaseId: databaseId(), path, newPassword, kdf });
+}
+
+export async function saveDatabaseCopy(
+  path: string,
+  newPassword?: string,
+  kdf?: KdfSettings
+): Promise<void> {
+  return await invoke<void>("save_database_copy", { databaseId: databaseId(), path, newPassword, kdf });
+}
+
+export async function setKdfSettings(settings: KdfSettings): Promise<void> {
+  return await invoke<void>("set_kdf_settings", { databaseId: databaseId(), settings });
+}
+
+export async function getKdfInfo(): Promise<KdfInfo> {
+  return await invoke<KdfInfo>("get_kdf_info", { databaseId: databaseId() });
+}
+
+export async function upgradeKdfParameters(): Promise<void> {
+  return await invoke<void>("upgrade_kdf_parameters", { databaseId: databaseId() });
+}
+
+export async function benchmarkKdf(
+  algorithm: KdfAlgorithm,
+  memory?: number,
+  parallelism?: number,
+  targetMs?: number
+): Promise<KdfSettings> {
+  return await invoke<KdfSettings>("benchmark_kdf", { algorithm, memory, parallelism, targetMs });
+}
+
+export async function getDatabaseSettings(): Promise<DatabaseSettings> {
+  return await invoke<DatabaseSettings>("get_database_settings", { databaseId: databaseId() });
+}
+
+export async function setDatabaseSettings(settings: DatabaseSettings): Promise<void> {
+  return await invoke<void>("set_database_settings", { databaseId: databaseId(), settings });
+}
+
+export async function getDatabaseMetadata(): Promise<DatabaseMetadata> {
+  return await invoke<DatabaseMetadata>("get_database_metadata", { databaseId: databaseId() });
+}
+
+export async function setDatabaseMetadata(metadata: DatabaseMetadata): Promise<void> {
+  return await invoke<void>("set_database_metadata", { databaseId: databaseId(), metadata });
+}
+
+export async function getMasterKeyStatus(): Promise<MasterKeyStatus> {
+  return await invoke<MasterKeyStatus>("get_master_key_status", { databaseId: databaseId() });
+}
+
+export async function setMasterKeyPolicy(
+  recommendAfterDays: number | null,
+  forceAfterDays: number | null
+): Promise<void> {
+  return await invoke<void>("set_master_key_policy", { databaseId: databaseId(), recommendAfterDays, forceAfterDays });
+}
+
+export async function changeMasterKey(newPassword: string): Promise<void> {
+  return await invoke<void>("change_master_key", { databaseId: databaseId(), newPassword });
 }
 
 export async function checkDatabaseChanges(): Promise<boolean> {
-  return await invoke<boolean>("check_database_changes");
+  return await invoke<boolean>("check_database_changes", { databaseId: databaseId() });
 }
 
 export async function mergeDatabase(): Promise<void> {
-  return await invoke<void>("merge_database");
+  return await invoke<void>("merge_database", { databaseId: databaseId() });
 }
 
 export async function closeDatabase(): Promise<void> {
-  return await invoke<void>("close_database");
+  await invoke<void>("close_database", { databaseId: databaseId() });
+  setActiveDatabaseId(null);
+}
+
+export async function getDatabaseStatus(): Promise<DatabaseStatus> {
+  return await invoke<DatabaseStatus>("get_database_status", { databaseId: databaseId() });
+}
+
+export async function onDatabaseChanged(handler: (event: DatabaseChanged) => void): Promise<UnlistenFn> {
+  return await listen<DatabaseChanged>("db-changed", (event) => handler(event.payload));
+}
+
+export async function onAutosaveError(handler: (message: string) => void): Promise<UnlistenFn> {
+  return await listen<{ message: string }>("autosave-error", (event) => handler(event.payload.message));
+}
+
+export async function getAutosaveSettings(): Promise<AutosaveSettings> {
+  return await invoke<AutosaveSettings>("get_autosave_settings");
+}
+
+export async function setAutosaveSettings(settings: AutosaveSettings): Promise<void> {
+  return await invoke<void>("set_autosave_settings", { settings });
+}
+
+export async function undo(): Promise<UndoState> {
+  return await invoke<UndoState>("undo", { databaseId: databaseId() });
+}
+
+export async function redo(): Promise<UndoState> {
+  return await invoke<UndoState>("redo", { databaseId: databaseId() });
+}
+
+export async function getUndoState(): Promise<UndoState> {
+  return await invoke<UndoState>("get_undo_state", { databaseId: databaseId() });
+}
+
+export async function setUndoSettings(maxDepth: number, keepAcrossSaves: boolean): Promise<UndoState> {
+  return await invoke<UndoState>("set_undo_settings", { databaseId: databaseId(), maxDepth, keepAcrossSaves });
 }
 
 export async function getGroups(): Promise<GroupData> {
-  return await invoke<GroupData>("get_groups");
+  return await invoke<GroupData>("get_groups", { databaseId: databaseId() });
 }
 
 export async function getEntries(groupUuid: string): Promise<EntryData[]> {
-  return await invoke<EntryData[]>("get_entries", { groupUuid });
+  return await invoke<EntryData[]>("get_entries", { databaseId: databaseId(), groupUuid });
 }
 
 export async function getFavoriteEntries(): Promise<EntryData[]> {
-  return await invoke<EntryData[]>("get_favorite_entries");
+  return await invoke<EntryData[]>("get_favorite_entries", { databaseId: databaseId() });
 }
 
 export async function getEntry(entryUuid: string): Promise<EntryData> {
-  return await invoke<EntryData>("get_entry", { entryUuid });
+  return await invoke<EntryData>("get_entry", { databaseId: databaseId(), entryUuid });
 }
 
 export async function createEntry(entry: EntryData): Promise<void> {
-  return await invoke<void>("create_entry", { entry });
+  return await invoke<void>("create_entry", { databaseId: databaseId(), entry });
 }
 
 export async function updateEntry(entry: EntryData): Promise<void> {
-  return await invoke<void>("update_entry", { entry });
+  return await invoke<void>("update_entry", { databaseId: databaseId(), entry });
 }
 
 export async function deleteEntry(entryUuid: string): Promise<void> {
-  return await invoke<void>("delete_entry", { entryUuid });
+  return await invoke<void>("delete_entry", { databaseId: databaseId(), entryUuid });
 }
 
 export async function moveEntry(entryUuid: string, newGroupUuid: string): Promise<void> {
-  return await invoke<void>("move_entry", { entryUuid, newGroupUuid });
+  return await invoke<void>("move_entry", { databaseId: databaseId(), entryUuid, newGroupUuid });
+}
+
+export async function recordEntryAccess(entryUuid: string, action: AccessAction): Promise<void> {
+  return await invoke<void>("record_entry_access", { databaseId: databaseId(), entryUuid, action });
+}
+
+export async function getMostUsedEntries(limit: number): Promise<EntryData[]> {
+  return await invoke<EntryData[]>("get_most_used_entries", { databaseId: databaseId(), limit });
 }
 
+export async function getRecentlyUsedEntries(limit: number): Promise<EntryData[]> {
+  return await invoke<EntryData[]>("get_recently_used_entries", { databaseId: databaseId(), limit });
+}
+
+export async function moveEntries(entryUuids: string[], newGroupUuid: string): Promise<BulkOperationResult> {
+  return await invoke<BulkOperationResult>("move_entries", { databaseId: databaseId(), entryUuids, newGroupUuid });
+}
+
+export async function deleteEntries(entryUuids: string[]): Promise<BulkOperationResult> {
+  return await invoke<BulkOperationResult>("delete_entries", { databaseId: databaseId(), entryUuids });
+}
+
+export async function addTagsToEntries(entryUuids: string[], tags: string[]): Promise<BulkOperationResult> {
+  return await invoke<BulkOperationResult>("add_tags_to_entries", { databaseId: databaseId(), entryUuids, tags });
+}
+
+export async function removeTagsFromEntries(entryUuids: string[], tags: string[]): Promise<BulkOperationResult> {
+  return await invoke<BulkOperationResult>("remove_tags_from_entries", { databaseId: databaseId(), entryUuids, tags });
+}
+
+export async function getAllTags(): Promise<TagCount[]> {
+  return await invoke<TagCount[]>("get_all_tags", { databaseId: databaseId() });
+}
+
+// Entries with every one of `tags`, or any of them when `matchAll` is false
+export async function getEntriesWithTags(tags: string[], matchAll = true): Promise<EntryData[]> {
+  return await invoke<EntryData[]>("get_entries_with_tags", { databaseId: databaseId(), tags, matchAll });
+}
+
+// The rename/merge/delete wrappers return the UUIDs of the entries that changed
+export async function renameTag(oldName: string, newName: string): Promise<string[]> {
+  return await invoke<string[]>("rename_tag", { databaseId: databaseId(), oldName, newName });
+}
+
+export async function mergeTags(source: string, target: string): Promise<string[]> {
+  return await invoke<string[]>("merge_tags", { databaseId: databaseId(), source, target });
+}
+
+export async function deleteTag(name: string): Promise<string[]> {
+  return await invoke<string[]>("delete_tag", { databaseId: databaseId(), name });
+}
+
+export async function setEntriesFavorite(entryUuids: string[], isFavorite: boolean): Promise<BulkOperationResult> {
+  return await invoke<BulkOperationResult>("set_entries_favorite", { databaseId: databaseId(), entryUuids, isFavorite });
+}
+
+export async function setEntriesExpiry(
+  entryUuids: string[],
+  expires: boolean,
+  expiryTime?: string
+): Promise<BulkOperationResult> {
+  return await invoke<BulkOperationResult>("set_entries_expiry", { databaseId: databaseId(), entryUuids, expires, expiryTime });
+}
+
+export async function setEntriesIcon(entryUuids: string[], iconId?: number): Promise<BulkOperationResult> {
+  return await invoke<BulkOperationResult>("set_entries_icon", { databaseId: databaseId(), entryUuids, iconId });
+}
 
 export async function createGroup(
   name: string,
   parentUuid: string | null,
   iconId?: number
 ): Promise<void> {
-  return await invoke<void>("create_group", { name, parentUuid, iconId });
+  return await invoke<void>("create_group", { databaseId: databaseId(), name, parentUuid, iconId });
 }
 
 export async function renameGroup(
@@ -132,33 +536,81 @@ export async function renameGroup(
   newName: string,
   iconId?: number
 ): Promise<void> {
-  return await invoke<void>("rename_group", { groupUuid, newName, iconId });
+  return await invoke<void>("rename_group", { databaseId: databaseId(), groupUuid, newName, iconId });
 }
 
 export async function moveGroup(
   groupUuid: string,
   newParentUuid: string
 ): Promise<void> {
-  return await invoke<void>("move_group", { groupUuid, newParentUuid });
+  return await invoke<void>("move_group", { databaseId: databaseId(), groupUuid, newParentUuid });
 }
 
 export async function reorderGroup(
   groupUuid: string,
   targetIndex: number
 ): Promise<void> {
-  return await invoke<void>("reorder_group", { groupUuid, targetIndex });
+  return await invoke<void>("reorder_group", { databaseId: databaseId(), groupUuid, targetIndex });
 }
 
 export async function deleteGroup(groupUuid: string): Promise<void> {
-  return await invoke<void>("delete_group", { groupUuid });
+  return await invoke<void>("delete_group", { databaseId: databaseId(), groupUuid });
 }
 
-export async function searchEntries(query: string): Promise<EntryData[]> {
-  return await invoke<EntryData[]>("search_entries", { query });
+// `null` makes the group inherit the setting from its parent
+export async function setGroupSearching(groupUuid: string, enabled: boolean | null): Promise<void> {
+  return await invoke<void>("set_group_searching", { databaseId: databaseId(), groupUuid, enabled });
 }
 
-export async function searchEntriesInGroup(query: string, groupUuid: string): Promise<EntryData[]> {
-  return await invoke<EntryData[]>("search_entries_in_group", { query, groupUuid });
+export async function setGroupAutoType(groupUuid: string, enabled: boolean | null): Promise<void> {
+  return await invoke<void>("set_group_auto_type", { databaseId: databaseId(), groupUuid, enabled });
+}
+
+export async function searchEntries(query: string, options?: SearchOptions): Promise<SearchResult[]> {
+  return await invoke<SearchResult[]>("search_entries", { databaseId: databaseId(), query, options });
+}
+
+// Spans every open vault unless `databaseIds` narrows it down
+export async function searchAllDatabases(
+  query: string,
+  databaseIds?: string[],
+  options?: SearchOptions
+): Promise<DatabaseSearchResult[]> {
+  return await invoke<DatabaseSearchResult[]>("search_all_databases", { query, databaseIds, options });
+}
+
+export async function searchEntriesInGroup(
+  query: string,
+  groupUuid: string,
+  options?: SearchOptions
+): Promise<SearchResult[]> {
+  return await invoke<SearchResult[]>("search_entries_in_group", { databaseId: databaseId(), query, groupUuid, options });
+}
+
+export async function getSavedSearches(): Promise<SavedSearch[]> {
+  return await invoke<SavedSearch[]>("get_saved_searches", { databaseId: databaseId() });
+}
+
+export async function createSavedSearch(name: string, query: string, options?: SearchOptions): Promise<SavedSearch> {
+  return await invoke<SavedSearch>("create_saved_search", { databaseId: databaseId(), name, query, options });
+}
+
+export async function updateSavedSearch(
+  searchUuid: string,
+  name: string,
+  query: string,
+  options?: SearchOptions
+): Promise<SavedSearch> {
+  return await invoke<SavedSearch>("update_saved_search", { databaseId: databaseId(), searchUuid, name, query, options });
+}
+
+export async function deleteSavedSearch(searchUuid: string): Promise<void> {
+  return await invoke<void>("delete_saved_search", { databaseId: databaseId(), searchUuid });
+}
+
+// The entries a saved search currently finds, best matches first
+export async function runSavedSearch(searchUuid: string): Promise<EntryData[]> {
+  return await invoke<EntryData[]>("run_saved_search", { databaseId: databaseId(), searchUuid });
 }
 
 export async function generatePassword(
@@ -178,9 +630,13 @@ export async function generatePassword(
 }
 
 export async function getDashboardStats(): Promise<DashboardStats> {
-  return await invoke<DashboardStats>("get_dashboard_stats");
+  return await invoke<DashboardStats>("get_dashboard_stats", { databaseId: databaseId() });
+}
+
+export async function getPasswordQuality(entryUuid: string): Promise<PasswordQuality> {
+  return await invoke<PasswordQuality>("password_quality", { databaseId: databaseId(), entryUuid });
 }
 
 export async function checkBreachedPasswords(): Promise<BreachedEntry[]> {
-  return await invoke<BreachedEntry[]>("check_breached_passwords");
+  return await invoke<BreachedEntry[]>("check_breached_passwords", { databaseId: databaseId() });
 }
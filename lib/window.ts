@@ -1,5 +1,5 @@
 import { WebviewWindow, getAllWebviewWindows } from "@tauri-apps/api/webviewWindow";
-import type { EntryData } from "@/lib/tauri";
+import { getActiveDatabaseId, type EntryData } from "@/lib/tauri";
 
 // Labels for child windows that should be closed when main app closes/logs out
 const CHILD_WINDOW_PREFIXES = ['entry-', 'settings'];
@@ -102,12 +102,13 @@ export async function openEntryWindow(entry: EntryData, groupUuid: string) {
     }
 
     // Create new window with localhost URL for development
-    // Pass group UUID as query parameter since sessionStorage doesn't work across Tauri windows
+    // Pass group UUID and database id as query parameters since sessionStorage doesn't work across Tauri windows
     const isDev = process.env.NODE_ENV === 'development';
     const baseUrl = isDev ? 'http://localhost:3000' : window.location.origin;
+    const databaseId = getActiveDatabaseId() ?? '';
     
     const webview = new WebviewWindow(windowLabel, {
-      url: `${baseUrl}/entry?uuid=${entry.uuid}&groupUuid=${encodeURIComponent(groupUuid)}`,
+      url: `${baseUrl}/entry?uuid=${entry.uuid}&groupUuid=${encodeURIComponent(groupUuid)}&databaseId=${encodeURIComponent(databaseId)}`,
       title: "Edit Entry",
       width: 500,
       height: 700,
@@ -0,0 +1,77 @@
+//! Compares the UUID index against the recursive tree walk it replaced.
+//!
+//! Run with `cargo bench --bench uuid_index`. The tree mimics a large vault:
+//! 10 top-level groups with 10 subgroups each, 100 entries per subgroup.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use keepass::db::{Entry, Group};
+use uuid::Uuid;
+
+#[allow(dead_code)]
+#[path = "../src/kdbx/tree.rs"]
+mod tree;
+
+// Its unit tests are compiled here without the test harness that would run them
+#[allow(dead_code, unused_imports)]
+#[path = "../src/kdbx/index.rs"]
+mod index;
+
+use index::TreeIndex;
+
+fn build_tree(groups: usize, subgroups: usize, entries: usize) -> (Group, Vec<Uuid>) {
+    let mut root = Group::new("Root");
+    let mut entry_uuids = Vec::new();
+
+    for g in 0..groups {
+        let mut group = Group::new(&format!("Group {}", g));
+        for s in 0..subgroups {
+            let mut subgroup = Group::new(&format!("Subgroup {}", s));
+            for _ in 0..entries {
+                let entry = Entry::new();
+                entry_uuids.push(entry.uuid);
+                subgroup.add_child(entry);
+            }
+            group.add_child(subgroup);
+        }
+        root.add_child(group);
+    }
+
+    (root, entry_uuids)
+}
+
+// The fallback Database uses when the index is stale
+fn find_entry_recursive<'a>(root: &'a Group, uuid: &Uuid) -> Option<&'a Entry> {
+    tree::entry_at(root, &tree::find_entry(root, uuid)?)
+}
+
+fn lookup(c: &mut Criterion) {
+    let (root, uuids) = build_tree(10, 10, 100);
+    let index = TreeIndex::build(&root);
+
+    // First, middle and last entry in walk order: best, typical and worst case for the walk
+    let samples = [
+        ("first", uuids[0]),
+        ("middle", uuids[uuids.len() / 2]),
+        ("last", uuids[uuids.len() - 1]),
+    ];
+
+    let mut group = c.benchmark_group("entry_lookup_10k");
+    for (name, uuid) in samples {
+        group.bench_with_input(BenchmarkId::new("recursive", name), &uuid, |b, uuid| {
+            b.iter(|| find_entry_recursive(black_box(&root), black_box(uuid)))
+        });
+        group.bench_with_input(BenchmarkId::new("index", name), &uuid, |b, uuid| {
+            b.iter(|| index.entry(black_box(&root), black_box(uuid)))
+        });
+    }
+    group.finish();
+}
+
+fn rebuild(c: &mut Criterion) {
+    // The index is rebuilt after every mutation, so this is the per-edit overhead
+    let (root, _) = build_tree(10, 10, 100);
+    c.bench_function("index_rebuild_10k", |b| b.iter(|| TreeIndex::build(black_box(&root))));
+}
+
+criterion_group!(benches, lookup, rebuild);
+criterion_main!(benches);
@@ -0,0 +1,183 @@
+use crate::events::{emit_db_changed, ChangeKind};
+use crate::state::AppState;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+pub const AUTOSAVE_ERROR_EVENT: &str = "autosave-error";
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AutosaveMode {
+    Off,
+    AfterChange,
+    Debounced,
+    OnLockClose,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AutosaveSettings {
+    pub mode: AutosaveMode,
+    pub debounce_seconds: u64,
+}
+
+impl Default for AutosaveSettings {
+    fn default() -> Self {
+        Self {
+            mode: AutosaveMode::Off,
+            debounce_seconds: 5,
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+struct AutosaveError {
+    message: String,
+}
+
+// Called after every mutation of any open database. Every call bumps the
+// generation, so a pending debounced save that sees a newer generation knows it
+// has been superseded; the one that survives saves every dirty database.
+pub fn schedule(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let settings = match state.autosave.lock() {
+        Ok(settings) => settings.clone(),
+        Err(e) => {
+            eprintln!("autosave::schedule: Lock poisoned: {}", e);
+            return;
+        }
+    };
+
+    let generation = state.autosave_generation.fetch_add(1, Ordering::SeqCst) + 1;
+    let delay = match settings.mode {
+        AutosaveMode::AfterChange => Duration::ZERO,
+        AutosaveMode::Debounced => Duration::from_secs(settings.debounce_seconds),
+        AutosaveMode::Off | AutosaveMode::OnLockClose => return,
+    };
+
+    // Save on a background thread so the command returns immediately
+    let app = app.clone();
+    std::thread::spawn(move || {
+        if !delay.is_zero() {
+            std::thread::sleep(delay);
+        }
+
+        let state = app.state::<AppState>();
+        if state.autosave_generation.load(Ordering::SeqCst) != generation {
+            return;
+        }
+
+        if let Err(e) = save_if_dirty(&app, None) {
+            report_error(&app, e);
+        }
+    });
+}
+
+// Used on lock/close: any mode except Off saves pending changes first.
+// `None` covers every open database, e.g. when the main window closes.
+pub fn save_before_close(app: &AppHandle, database_id: Option<&str>) -> Result<(), String> {
+    let enabled = app
+        .state::<AppState>()
+        .autosave
+        .lock()
+        .map(|settings| settings.mode != AutosaveMode::Off)
+        .map_err(|e| {
+            eprintln!("autosave::save_before_close: Lock poisoned: {}", e);
+            "Failed to access autosave settings".to_string()
+        })?;
+
+    if !enabled {
+        return Ok(());
+    }
+
+    save_if_dirty(app, database_id).inspect_err(|e| report_error(app, e.clone()))
+}
+
+fn save_if_dirty(app: &AppHandle, database_id: Option<&str>) -> Result<(), String> {
+    let state = app.state::<AppState>();
+
+    // Serializes all writers of the database file; always taken before the database lock
+    let _save_guard = state.save_lock.lock()
+        .map_err(|e| {
+            eprintln!("autosave: Save lock poisoned: {}", e);
+            "Failed to access save lock".to_string()
+        })?;
+
+    let database_ids: Vec<String> = match database_id {
+        Some(id) => vec![id.to_string()],
+        None => state.databases.read()
+            .map_err(|e| {
+                eprintln!("autosave: Lock poisoned: {}", e);
+                "Failed to access database state".to_string()
+            })?
+            .keys()
+            .cloned()
+            .collect(),
+    };
+
+    // Keep going after a failure so one unwritable vault doesn't block the others
+    let errors: Vec<String> = database_ids
+        .iter()
+        .filter_map(|id| write_database(app, &state, id, true).err())
+        .collect();
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join("\n"))
+    }
+}
+
+/// Saves one database now, e.g. for the save command. Runs the KDF, so call it
+/// from a blocking thread rather than the main thread.
+pub fn save_now(app: &AppHandle, database_id: &str) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let _save_guard = state.save_lock.lock()
+        .map_err(|e| {
+            eprintln!("autosave::save_now: Save lock poisoned: {}", e);
+            "Failed to access save lock".to_string()
+        })?;
+
+    write_database(app, &state, database_id, false)
+}
+
+// The database lock is only held to take the snapshot and to record the result,
+// never while the KDF runs; other commands keep working during a slow save.
+// Callers must hold the save lock.
+fn write_database(app: &AppHandle, state: &AppState, database_id: &str, only_if_dirty: bool) -> Result<(), String> {
+    let pending = {
+        let databases = state.databases.read()
+            .map_err(|e| {
+                eprintln!("autosave: Lock poisoned: {}", e);
+                "Failed to access database state".to_string()
+            })?;
+        match databases.get(database_id) {
+            Some(db) if only_if_dirty && !db.is_dirty() => return Ok(()),
+            Some(db) => db.prepare_save().map_err(|e| e.to_string())?,
+            // Closed since the save was scheduled
+            None if only_if_dirty => return Ok(()),
+            None => return Err("No database loaded".to_string()),
+        }
+    };
+
+    pending.write().map_err(|e| e.to_string())?;
+
+    let mut databases = state.databases.write()
+        .map_err(|e| {
+            eprintln!("autosave: Lock poisoned: {}", e);
+            "Failed to access database state".to_string()
+        })?;
+    if let Some(db) = databases.get_mut(database_id) {
+        db.finish_save(pending);
+        emit_db_changed(app, database_id, db, ChangeKind::Saved, Vec::new());
+    }
+    Ok(())
+}
+
+fn report_error(app: &AppHandle, message: String) {
+    eprintln!("autosave: Save failed: {}", message);
+    if let Err(e) = app.emit(AUTOSAVE_ERROR_EVENT, AutosaveError { message }) {
+        eprintln!("autosave: Failed to emit error event: {}", e);
+    }
+}
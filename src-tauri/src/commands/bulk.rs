@@ -0,0 +1,186 @@
+use crate::events::{emit_db_changed, ChangeKind};
+use crate::kdbx::BulkOperationResult;
+use crate::state::AppState;
+use tauri::{AppHandle, State};
+
+#[tauri::command]
+pub fn move_entries(
+    app: AppHandle,
+    state: State<AppState>,
+    database_id: String,
+    entry_uuids: Vec<String>,
+    new_group_uuid: String,
+) -> Result<BulkOperationResult, String> {
+    let mut databases = state.databases.write()
+        .map_err(|e| {
+            eprintln!("move_entries: Lock poisoned: {}", e);
+            "Failed to access database state".to_string()
+        })?;
+
+    if let Some(db) = databases.get_mut(&database_id) {
+        let result = db.move_entries(&entry_uuids, &new_group_uuid).map_err(|e| e.to_string())?;
+        if result.applied {
+            let uuids = result.results.iter().map(|r| r.uuid.clone()).collect();
+            emit_db_changed(&app, &database_id, db, ChangeKind::EntryMoved, uuids);
+        }
+        Ok(result)
+    } else {
+        Err("No database loaded".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn delete_entries(
+    app: AppHandle,
+    state: State<AppState>,
+    database_id: String,
+    entry_uuids: Vec<String>,
+) -> Result<BulkOperationResult, String> {
+    let mut databases = state.databases.write()
+        .map_err(|e| {
+            eprintln!("delete_entries: Lock poisoned: {}", e);
+            "Failed to access database state".to_string()
+        })?;
+
+    if let Some(db) = databases.get_mut(&database_id) {
+        let result = db.delete_entries(&entry_uuids).map_err(|e| e.to_string())?;
+        if result.applied {
+            let uuids = result.results.iter().map(|r| r.uuid.clone()).collect();
+            emit_db_changed(&app, &database_id, db, ChangeKind::EntryDeleted, uuids);
+        }
+        Ok(result)
+    } else {
+        Err("No database loaded".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn add_tags_to_entries(
+    app: AppHandle,
+    state: State<AppState>,
+    database_id: String,
+    entry_uuids: Vec<String>,
+    tags: Vec<String>,
+) -> Result<BulkOperationResult, String> {
+    let mut databases = state.databases.write()
+        .map_err(|e| {
+            eprintln!("add_tags_to_entries: Lock poisoned: {}", e);
+            "Failed to access database state".to_string()
+        })?;
+
+    if let Some(db) = databases.get_mut(&database_id) {
+        let result = db.add_tags_to_entries(&entry_uuids, &tags).map_err(|e| e.to_string())?;
+        if result.applied {
+            let uuids = result.results.iter().map(|r| r.uuid.clone()).collect();
+            emit_db_changed(&app, &database_id, db, ChangeKind::EntryUpdated, uuids);
+        }
+        Ok(result)
+    } else {
+        Err("No database loaded".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn remove_tags_from_entries(
+    app: AppHandle,
+    state: State<AppState>,
+    database_id: String,
+    entry_uuids: Vec<String>,
+    tags: Vec<String>,
+) -> Result<BulkOperationResult, String> {
+    let mut databases = state.databases.write()
+        .map_err(|e| {
+            eprintln!("remove_tags_from_entries: Lock poisoned: {}", e);
+            "Failed to access database state".to_string()
+        })?;
+
+    if let Some(db) = databases.get_mut(&database_id) {
+        let result = db.remove_tags_from_entries(&entry_uuids, &tags).map_err(|e| e.to_string())?;
+        if result.applied {
+            let uuids = result.results.iter().map(|r| r.uuid.clone()).collect();
+            emit_db_changed(&app, &database_id, db, ChangeKind::EntryUpdated, uuids);
+        }
+        Ok(result)
+    } else {
+        Err("No database loaded".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn set_entries_favorite(
+    app: AppHandle,
+    state: State<AppState>,
+    database_id: String,
+    entry_uuids: Vec<String>,
+    is_favorite: bool,
+) -> Result<BulkOperationResult, String> {
+    let mut databases = state.databases.write()
+        .map_err(|e| {
+            eprintln!("set_entries_favorite: Lock poisoned: {}", e);
+            "Failed to access database state".to_string()
+        })?;
+
+    if let Some(db) = databases.get_mut(&database_id) {
+        let result = db.set_entries_favorite(&entry_uuids, is_favorite).map_err(|e| e.to_string())?;
+        if result.applied {
+            let uuids = result.results.iter().map(|r| r.uuid.clone()).collect();
+            emit_db_changed(&app, &database_id, db, ChangeKind::EntryUpdated, uuids);
+        }
+        Ok(result)
+    } else {
+        Err("No database loaded".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn set_entries_expiry(
+    app: AppHandle,
+    state: State<AppState>,
+    database_id: String,
+    entry_uuids: Vec<String>,
+    expires: bool,
+    expiry_time: Option<String>,
+) -> Result<BulkOperationResult, String> {
+    let mut databases = state.databases.write()
+        .map_err(|e| {
+            eprintln!("set_entries_expiry: Lock poisoned: {}", e);
+            "Failed to access database state".to_string()
+        })?;
+
+    if let Some(db) = databases.get_mut(&database_id) {
+        let result = db.set_entries_expiry(&entry_uuids, expires, expiry_time).map_err(|e| e.to_string())?;
+        if result.applied {
+            let uuids = result.results.iter().map(|r| r.uuid.clone()).collect();
+            emit_db_changed(&app, &database_id, db, ChangeKind::EntryUpdated, uuids);
+        }
+        Ok(result)
+    } else {
+        Err("No database loaded".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn set_entries_icon(
+    app: AppHandle,
+    state: State<AppState>,
+    database_id: String,
+    entry_uuids: Vec<String>,
+    icon_id: Option<usize>,
+) -> Result<BulkOperationResult, String> {
+    let mut databases = state.databases.write()
+        .map_err(|e| {
+            eprintln!("set_entries_icon: Lock poisoned: {}", e);
+            "Failed to access database state".to_string()
+        })?;
+
+    if let Some(db) = databases.get_mut(&database_id) {
+        let result = db.set_entries_icon(&entry_uuids, icon_id).map_err(|e| e.to_string())?;
+        if result.applied {
+            let uuids = result.results.iter().map(|r| r.uuid.clone()).collect();
+            emit_db_changed(&app, &database_id, db, ChangeKind::EntryUpdated, uuids);
+        }
+        Ok(result)
+    } else {
+        Err("No database loaded".to_string())
+    }
+}
@@ -1,7 +1,22 @@
-use crate::kdbx::{Database, GroupData, KdfInfo};
+use crate::autosave::{self, AutosaveSettings};
+use crate::events::{emit_db_changed, ChangeKind};
+use crate::kdbx::{Database, DatabaseMetadata, DatabaseSettings, DatabaseStatus, GroupData, KdfAlgorithm, KdfInfo, KdfSettings, LockFile, LockInfo, LockStatus, MasterKeyStatus, UndoState};
 use crate::state::AppState;
-use std::path::PathBuf;
-use tauri::State;
+use crate::watcher;
+use secrecy::{ExposeSecret, SecretString};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+use tauri::{AppHandle, Manager, State};
+
+#[derive(Clone, Serialize)]
+pub struct OpenDatabaseInfo {
+    pub id: String,
+    pub path: String,
+    pub name: String,
+    pub dirty: bool,
+    pub read_only: bool,
+}
 
 #[tauri::command]
 pub fn get_initial_file_path(state: State<AppState>) -> Option<String> {
@@ -26,56 +41,350 @@ pub fn clear_initial_file_path(state: State<AppState>) -> Result<(), String> {
 }
 
 #[tauri::command]
-pub fn create_database(
-    state: State<AppState>,
+pub fn list_open_databases(state: State<AppState>) -> Result<Vec<OpenDatabaseInfo>, String> {
+    let databases = state.databases.read()
+        .map_err(|e| {
+            eprintln!("list_open_databases: Lock poisoned: {}", e);
+            "Failed to access database state".to_string()
+        })?;
+
+    let mut open: Vec<OpenDatabaseInfo> = databases
+        .iter()
+        .map(|(id, db)| OpenDatabaseInfo {
+            id: id.clone(),
+            path: db.path.to_string_lossy().to_string(),
+            name: db.get_metadata().name,
+            dirty: db.is_dirty(),
+            read_only: db.is_read_only(),
+        })
+        .collect();
+    open.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(open)
+}
+
+// Opening and creating run Argon2, which can take seconds with strong settings.
+// Both run on the blocking pool so the UI thread stays responsive meanwhile.
+#[tauri::command]
+pub async fn create_database(
+    app: AppHandle,
     path: String,
     password: String,
-) -> Result<GroupData, String> {
-    let db = Database::create(PathBuf::from(&path), password).map_err(|e| e.to_string())?;
+) -> Result<(String, GroupData), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app.state::<AppState>();
+        let path_buf = PathBuf::from(&path);
+        ensure_not_open(&state, &path_buf)?;
+        let db = Database::create(path_buf.clone(), password).map_err(|e| e.to_string())?;
+
+        let root_group = db.get_root_group();
+        let database_id = insert_database(&state, db)?;
+
+        start_watcher(&app, &state, &database_id, &path_buf);
+
+        Ok((database_id, root_group))
+    })
+    .await
+    .map_err(|e| format!("Failed to create database: {}", e))?
+}
+
+#[tauri::command]
+pub async fn open_database(
+    app: AppHandle,
+    path: String,
+    password: String,
+    read_only: Option<bool>,
+) -> Result<(String, GroupData, String, MasterKeyStatus, Option<LockInfo>), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app.state::<AppState>();
+        let path_buf = PathBuf::from(&path);
+        ensure_not_open(&state, &path_buf)?;
+        // No lock is held here, so other open vaults stay usable while this one unlocks
+        let db = Database::open(path_buf.clone(), password, read_only.unwrap_or(false))
+            .map_err(|e| e.to_string())?;
+
+        let root_group = db.get_root_group();
+        // Returned up front so the UI can prompt for a key change before anything else
+        let key_status = db.get_master_key_status();
+        // Returned rather than emitted: nothing is listening before the vault is open
+        let replaced_lock = db.replaced_lock();
+        let database_id = insert_database(&state, db)?;
+
+        start_watcher(&app, &state, &database_id, &path_buf);
+
+        Ok((database_id, root_group, path, key_status, replaced_lock))
+    })
+    .await
+    .map_err(|e| format!("Failed to open database: {}", e))?
+}
+
+// Two handles on one file would each believe they own its lock file
+fn ensure_not_open(state: &State<AppState>, path: &Path) -> Result<(), String> {
+    let databases = state.databases.read()
+        .map_err(|e| {
+            eprintln!("ensure_not_open: Lock poisoned: {}", e);
+            "Failed to access database state".to_string()
+        })?;
 
-    let root_group = db.get_root_group();
+    if databases.values().any(|db| db.is_at(path)) {
+        Err("This database is already open".to_string())
+    } else {
+        Ok(())
+    }
+}
 
-    let mut database_lock = state.database.lock()
+// ensure_not_open runs before the KDF, so two opens of one file can both get here;
+// the one that loses is dropped, releasing anything it took
+fn insert_database(state: &State<AppState>, db: Database) -> Result<String, String> {
+    let database_id = uuid::Uuid::new_v4().to_string();
+    let mut databases = state.databases.write()
         .map_err(|e| {
-            eprintln!("create_database: Lock poisoned: {}", e);
+            eprintln!("insert_database: Lock poisoned: {}", e);
             "Failed to access database state".to_string()
         })?;
-    *database_lock = Some(db);
+    if databases.values().any(|open| open.is_at(&db.path)) {
+        return Err("This database is already open".to_string());
+    }
+    databases.insert(database_id.clone(), db);
+    Ok(database_id)
+}
+
+// A watcher failure is not fatal; check_database_changes still works as a fallback
+fn start_watcher(app: &AppHandle, state: &State<AppState>, database_id: &str, path: &Path) {
+    let new_watcher = watcher::start(app, database_id, path)
+        .map_err(|e| eprintln!("start_watcher: {}", e))
+        .ok();
 
-    Ok(root_group)
+    match state.watchers.lock() {
+        Ok(mut watchers) => match new_watcher {
+            Some(new_watcher) => {
+                watchers.insert(database_id.to_string(), new_watcher);
+            }
+            None => {
+                watchers.remove(database_id);
+            }
+        },
+        Err(e) => eprintln!("start_watcher: Lock poisoned: {}", e),
+    }
 }
 
 #[tauri::command]
-pub fn open_database(
-    state: State<AppState>,
+pub async fn save_database(app: AppHandle, database_id: String) -> Result<(), String> {
+    // Serializes with autosave and only holds the database lock around the KDF, not during it
+    tauri::async_runtime::spawn_blocking(move || autosave::save_now(&app, &database_id))
+        .await
+        .map_err(|e| format!("Failed to save database: {}", e))?
+}
+
+#[tauri::command]
+pub async fn save_database_as(
+    app: AppHandle,
+    database_id: String,
     path: String,
-    password: String,
-) -> Result<(GroupData, String), String> {
-    let path_buf = PathBuf::from(&path);
-    let db = Database::open(path_buf.clone(), password).map_err(|e| e.to_string())?;
+    new_password: Option<String>,
+    kdf: Option<KdfSettings>,
+) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app.state::<AppState>();
+        let path_buf = PathBuf::from(&path);
+        let _save_guard = state.save_lock.lock()
+            .map_err(|e| {
+                eprintln!("save_database_as: Save lock poisoned: {}", e);
+                "Failed to access database state".to_string()
+            })?;
+
+        let pending = {
+            let databases = state.databases.read()
+                .map_err(|e| {
+                    eprintln!("save_database_as: Lock poisoned: {}", e);
+                    "Failed to access database state".to_string()
+                })?;
+
+            if databases.iter().any(|(id, db)| *id != database_id && db.is_at(&path_buf)) {
+                return Err("Another open database already uses this file".to_string());
+            }
+            let db = databases.get(&database_id).ok_or("No database loaded".to_string())?;
+            db.prepare_save_as(path_buf.clone(), new_password, kdf).map_err(|e| e.to_string())?
+        };
+
+        pending.write().map_err(|e| e.to_string())?;
+
+        {
+            let mut databases = state.databases.write()
+                .map_err(|e| {
+                    eprintln!("save_database_as: Lock poisoned: {}", e);
+                    "Failed to access database state".to_string()
+                })?;
+            let db = databases.get_mut(&database_id).ok_or("No database loaded".to_string())?;
+            db.finish_save_as(pending);
+            emit_db_changed(&app, &database_id, db, ChangeKind::Saved, Vec::new());
+        }
+
+        // Follow the database to its new location
+        start_watcher(&app, &state, &database_id, &path_buf);
+        Ok(())
+    })
+    .await
+    .map_err(|e| format!("Failed to save database: {}", e))?
+}
+
+#[tauri::command]
+pub async fn save_database_copy(
+    app: AppHandle,
+    database_id: String,
+    path: String,
+    new_password: Option<String>,
+    kdf: Option<KdfSettings>,
+) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app.state::<AppState>();
+        let path_buf = PathBuf::from(&path);
+        let _save_guard = state.save_lock.lock()
+            .map_err(|e| {
+                eprintln!("save_database_copy: Save lock poisoned: {}", e);
+                "Failed to access database state".to_string()
+            })?;
 
-    let root_group = db.get_root_group();
+        let pending = {
+            let databases = state.databases.read()
+                .map_err(|e| {
+                    eprintln!("save_database_copy: Lock poisoned: {}", e);
+                    "Failed to access database state".to_string()
+                })?;
 
-    let mut database_lock = state.database.lock()
+            // Writing over another open vault would clobber it behind its back
+            if databases.iter().any(|(id, db)| *id != database_id && db.is_at(&path_buf)) {
+                return Err("Another open database already uses this file".to_string());
+            }
+            let db = databases.get(&database_id).ok_or("No database loaded".to_string())?;
+            db.prepare_copy(path_buf, new_password, kdf).map_err(|e| e.to_string())?
+        };
+
+        pending.write().map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Failed to save copy: {}", e))?
+}
+
+#[tauri::command]
+pub async fn close_database(app: AppHandle, database_id: String) -> Result<(), String> {
+    // Saving pending changes on close may run the KDF
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app.state::<AppState>();
+        autosave::save_before_close(&app, Some(&database_id))?;
+
+        if let Ok(mut watchers) = state.watchers.lock() {
+            watchers.remove(&database_id);
+        }
+
+        let mut databases = state.databases.write()
+            .map_err(|e| {
+                eprintln!("close_database: Lock poisoned: {}", e);
+                "Failed to access database state".to_string()
+            })?;
+        databases.remove(&database_id);
+        Ok(())
+    })
+    .await
+    .map_err(|e| format!("Failed to close database: {}", e))?
+}
+
+#[tauri::command]
+pub fn get_kdf_info(state: State<AppState>, database_id: String) -> Result<KdfInfo, String> {
+    let databases = state.databases.read()
         .map_err(|e| {
-            eprintln!("open_database: Lock poisoned: {}", e);
+            eprintln!("get_kdf_info: Lock poisoned: {}", e);
             "Failed to access database state".to_string()
         })?;
-    *database_lock = Some(db);
+    if let Some(db) = databases.get(&database_id) {
+        Ok(db.get_kdf_info())
+    } else {
+        Err("No database loaded".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn check_database_changes(state: State<AppState>, database_id: String) -> Result<bool, String> {
+    let databases = state.databases.read()
+        .map_err(|e| {
+            eprintln!("check_database_changes: Lock poisoned: {}", e);
+            "Failed to access database state".to_string()
+        })?;
+
+    if let Some(db) = databases.get(&database_id) {
+        db.check_for_changes().map_err(|e| e.to_string())
+    } else {
+        Err("No database loaded".to_string())
+    }
+}
 
-    Ok((root_group, path))
+#[tauri::command]
+pub async fn merge_database(app: AppHandle, database_id: String) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app.state::<AppState>();
+        let (path, password) = {
+            let databases = state.databases.read()
+                .map_err(|e| {
+                    eprintln!("merge_database: Lock poisoned: {}", e);
+                    "Failed to access database state".to_string()
+                })?;
+            let db = databases.get(&database_id).ok_or("No database loaded".to_string())?;
+            (db.path.clone(), SecretString::new(db.password.expose_secret().clone()))
+        };
+
+        // Decrypting the file on disk runs the KDF; no lock is held meanwhile
+        let disk_copy = Database::read_disk_copy(&path, &password).map_err(|e| e.to_string())?;
+
+        let mut databases = state.databases.write()
+            .map_err(|e| {
+                eprintln!("merge_database: Lock poisoned: {}", e);
+                "Failed to access database state".to_string()
+            })?;
+        let db = databases.get_mut(&database_id).ok_or("No database loaded".to_string())?;
+        db.merge_disk_copy(disk_copy).map_err(|e| e.to_string())?;
+        emit_db_changed(&app, &database_id, db, ChangeKind::Merged, Vec::new());
+        Ok(())
+    })
+    .await
+    .map_err(|e| format!("Failed to merge database: {}", e))?
 }
 
 #[tauri::command]
-pub fn save_database(state: State<AppState>) -> Result<(), String> {
-    let mut database_lock = state.database.lock()
+pub async fn upgrade_kdf_parameters(app: AppHandle, database_id: String) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app.state::<AppState>();
+        {
+            let mut databases = state.databases.write()
+                .map_err(|e| {
+                    eprintln!("upgrade_kdf_parameters: Lock poisoned: {}", e);
+                    "Failed to access database state".to_string()
+                })?;
+            let db = databases.get_mut(&database_id).ok_or("No database loaded".to_string())?;
+            db.upgrade_kdf_parameters().map_err(|e| e.to_string())?;
+        }
+
+        // The new parameters only protect the file once it is rewritten
+        autosave::save_now(&app, &database_id)
+    })
+    .await
+    .map_err(|e| format!("Failed to upgrade KDF parameters: {}", e))?
+}
+
+#[tauri::command]
+pub fn set_kdf_settings(
+    app: AppHandle,
+    state: State<AppState>,
+    database_id: String,
+    settings: KdfSettings,
+) -> Result<(), String> {
+    let mut databases = state.databases.write()
         .map_err(|e| {
-            eprintln!("save_database: Lock poisoned: {}", e);
+            eprintln!("set_kdf_settings: Lock poisoned: {}", e);
             "Failed to access database state".to_string()
         })?;
 
-    if let Some(db) = database_lock.as_mut() {
-        db.save().map_err(|e| e.to_string())?;
+    if let Some(db) = databases.get_mut(&database_id) {
+        db.set_kdf_settings(settings).map_err(|e| e.to_string())?;
+        emit_db_changed(&app, &database_id, db, ChangeKind::SettingsChanged, Vec::new());
         Ok(())
     } else {
         Err("No database loaded".to_string())
@@ -83,55 +392,195 @@ pub fn save_database(state: State<AppState>) -> Result<(), String> {
 }
 
 #[tauri::command]
-pub fn close_database(state: State<AppState>) -> Result<(), String> {
-    let mut database_lock = state.database.lock()
+pub async fn benchmark_kdf(
+    algorithm: KdfAlgorithm,
+    memory: Option<u64>,
+    parallelism: Option<u32>,
+    target_ms: Option<u64>,
+) -> Result<KdfSettings, String> {
+    let memory = memory.unwrap_or(64 * 1024 * 1024);
+    let parallelism = parallelism.unwrap_or(2);
+    let target = std::time::Duration::from_millis(target_ms.unwrap_or(1000));
+
+    // The benchmark burns CPU for about `target`; keep it off the async runtime
+    tauri::async_runtime::spawn_blocking(move || {
+        KdfSettings::benchmark(algorithm, memory, parallelism, target)
+    })
+    .await
+    .map_err(|e| format!("Benchmark failed: {}", e))?
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_groups(state: State<AppState>, database_id: String) -> Result<GroupData, String> {
+    let databases = state.databases.read()
         .map_err(|e| {
-            eprintln!("close_database: Lock poisoned: {}", e);
+            eprintln!("get_groups: Lock poisoned: {}", e);
             "Failed to access database state".to_string()
         })?;
-    *database_lock = None;
-    Ok(())
+
+    if let Some(db) = databases.get(&database_id) {
+        Ok(db.get_root_group())
+    } else {
+        Err("No database loaded".to_string())
+    }
 }
 
 #[tauri::command]
-pub fn get_kdf_info(state: State<AppState>) -> Result<KdfInfo, String> {
-    let database_lock = state.database.lock()
+pub fn undo(app: AppHandle, state: State<AppState>, database_id: String) -> Result<UndoState, String> {
+    let mut databases = state.databases.write()
         .map_err(|e| {
-            eprintln!("get_kdf_info: Lock poisoned: {}", e);
+            eprintln!("undo: Lock poisoned: {}", e);
             "Failed to access database state".to_string()
         })?;
-    if let Some(db) = database_lock.as_ref() {
-        Ok(db.get_kdf_info())
+
+    if let Some(db) = databases.get_mut(&database_id) {
+        let undo_state = db.undo().map_err(|e| e.to_string())?;
+        emit_db_changed(&app, &database_id, db, ChangeKind::Undone, Vec::new());
+        Ok(undo_state)
     } else {
         Err("No database loaded".to_string())
     }
 }
 
 #[tauri::command]
-pub fn check_database_changes(state: State<AppState>) -> Result<bool, String> {
-    let database_lock = state.database.lock()
+pub fn redo(app: AppHandle, state: State<AppState>, database_id: String) -> Result<UndoState, String> {
+    let mut databases = state.databases.write()
         .map_err(|e| {
-            eprintln!("check_database_changes: Lock poisoned: {}", e);
+            eprintln!("redo: Lock poisoned: {}", e);
             "Failed to access database state".to_string()
         })?;
 
-    if let Some(db) = database_lock.as_ref() {
-        db.check_for_changes().map_err(|e| e.to_string())
+    if let Some(db) = databases.get_mut(&database_id) {
+        let undo_state = db.redo().map_err(|e| e.to_string())?;
+        emit_db_changed(&app, &database_id, db, ChangeKind::Redone, Vec::new());
+        Ok(undo_state)
+    } else {
+        Err("No database loaded".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn get_undo_state(state: State<AppState>, database_id: String) -> Result<UndoState, String> {
+    let databases = state.databases.read()
+        .map_err(|e| {
+            eprintln!("get_undo_state: Lock poisoned: {}", e);
+            "Failed to access database state".to_string()
+        })?;
+
+    if let Some(db) = databases.get(&database_id) {
+        Ok(db.get_undo_state())
+    } else {
+        Err("No database loaded".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn set_undo_settings(
+    state: State<AppState>,
+    database_id: String,
+    max_depth: usize,
+    keep_across_saves: bool,
+) -> Result<UndoState, String> {
+    let mut databases = state.databases.write()
+        .map_err(|e| {
+            eprintln!("set_undo_settings: Lock poisoned: {}", e);
+            "Failed to access database state".to_string()
+        })?;
+
+    if let Some(db) = databases.get_mut(&database_id) {
+        Ok(db.set_undo_settings(max_depth, keep_across_saves))
+    } else {
+        Err("No database loaded".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn get_database_status(state: State<AppState>, database_id: String) -> Result<DatabaseStatus, String> {
+    let databases = state.databases.read()
+        .map_err(|e| {
+            eprintln!("get_database_status: Lock poisoned: {}", e);
+            "Failed to access database state".to_string()
+        })?;
+
+    if let Some(db) = databases.get(&database_id) {
+        Ok(db.get_status())
+    } else {
+        Err("No database loaded".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn get_autosave_settings(state: State<AppState>) -> Result<AutosaveSettings, String> {
+    let settings = state.autosave.lock()
+        .map_err(|e| {
+            eprintln!("get_autosave_settings: Lock poisoned: {}", e);
+            "Failed to access autosave settings".to_string()
+        })?;
+    Ok(settings.clone())
+}
+
+#[tauri::command]
+pub fn set_autosave_settings(state: State<AppState>, settings: AutosaveSettings) -> Result<(), String> {
+    if settings.debounce_seconds == 0 {
+        return Err("Debounce interval must be at least one second".to_string());
+    }
+
+    let mut current = state.autosave.lock()
+        .map_err(|e| {
+            eprintln!("set_autosave_settings: Lock poisoned: {}", e);
+            "Failed to access autosave settings".to_string()
+        })?;
+    *current = settings;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_database_lock_status(path: String) -> LockStatus {
+    LockFile::status(&PathBuf::from(path))
+}
+
+#[tauri::command]
+pub fn break_database_lock(path: String) -> Result<(), String> {
+    LockFile::break_lock(&PathBuf::from(path)).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_auto_merge_external_changes(state: State<AppState>, enabled: bool) {
+    state.auto_merge_external_changes.store(enabled, Ordering::SeqCst);
+}
+
+#[tauri::command]
+pub fn get_database_settings(state: State<AppState>, database_id: String) -> Result<DatabaseSettings, String> {
+    let databases = state.databases.read()
+        .map_err(|e| {
+            eprintln!("get_database_settings: Lock poisoned: {}", e);
+            "Failed to access database state".to_string()
+        })?;
+
+    if let Some(db) = databases.get(&database_id) {
+        Ok(db.get_database_settings())
     } else {
         Err("No database loaded".to_string())
     }
 }
 
 #[tauri::command]
-pub fn merge_database(state: State<AppState>) -> Result<(), String> {
-    let mut database_lock = state.database.lock()
+pub fn set_database_settings(
+    app: AppHandle,
+    state: State<AppState>,
+    database_id: String,
+    settings: DatabaseSettings,
+) -> Result<(), String> {
+    let mut databases = state.databases.write()
         .map_err(|e| {
-            eprintln!("merge_database: Lock poisoned: {}", e);
+            eprintln!("set_database_settings: Lock poisoned: {}", e);
             "Failed to access database state".to_string()
         })?;
 
-    if let Some(db) = database_lock.as_mut() {
-        db.merge_database().map_err(|e| e.to_string())?;
+    if let Some(db) = databases.get_mut(&database_id) {
+        db.set_database_settings(settings).map_err(|e| e.to_string())?;
+        emit_db_changed(&app, &database_id, db, ChangeKind::SettingsChanged, Vec::new());
         Ok(())
     } else {
         Err("No database loaded".to_string())
@@ -139,14 +588,36 @@ pub fn merge_database(state: State<AppState>) -> Result<(), String> {
 }
 
 #[tauri::command]
-pub fn upgrade_kdf_parameters(state: State<AppState>) -> Result<(), String> {
-    let mut database_lock = state.database.lock()
+pub fn get_database_metadata(state: State<AppState>, database_id: String) -> Result<DatabaseMetadata, String> {
+    let databases = state.databases.read()
+        .map_err(|e| {
+            eprintln!("get_database_metadata: Lock poisoned: {}", e);
+            "Failed to access database state".to_string()
+        })?;
+
+    if let Some(db) = databases.get(&database_id) {
+        Ok(db.get_metadata())
+    } else {
+        Err("No database loaded".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn set_database_metadata(
+    app: AppHandle,
+    state: State<AppState>,
+    database_id: String,
+    metadata: DatabaseMetadata,
+) -> Result<(), String> {
+    let mut databases = state.databases.write()
         .map_err(|e| {
-            eprintln!("upgrade_kdf_parameters: Lock poisoned: {}", e);
+            eprintln!("set_database_metadata: Lock poisoned: {}", e);
             "Failed to access database state".to_string()
         })?;
-    if let Some(db) = database_lock.as_mut() {
-        db.upgrade_kdf_parameters().map_err(|e| e.to_string())?;
+
+    if let Some(db) = databases.get_mut(&database_id) {
+        db.set_metadata(metadata).map_err(|e| e.to_string())?;
+        emit_db_changed(&app, &database_id, db, ChangeKind::SettingsChanged, Vec::new());
         Ok(())
     } else {
         Err("No database loaded".to_string())
@@ -154,16 +625,83 @@ pub fn upgrade_kdf_parameters(state: State<AppState>) -> Result<(), String> {
 }
 
 #[tauri::command]
-pub fn get_groups(state: State<AppState>) -> Result<GroupData, String> {
-    let database_lock = state.database.lock()
+pub fn get_master_key_status(state: State<AppState>, database_id: String) -> Result<MasterKeyStatus, String> {
+    let databases = state.databases.read()
         .map_err(|e| {
-            eprintln!("get_groups: Lock poisoned: {}", e);
+            eprintln!("get_master_key_status: Lock poisoned: {}", e);
             "Failed to access database state".to_string()
         })?;
 
-    if let Some(db) = database_lock.as_ref() {
-        Ok(db.get_root_group())
+    if let Some(db) = databases.get(&database_id) {
+        Ok(db.get_master_key_status())
     } else {
         Err("No database loaded".to_string())
     }
 }
+
+#[tauri::command]
+pub fn set_master_key_policy(
+    app: AppHandle,
+    state: State<AppState>,
+    database_id: String,
+    recommend_after_days: Option<i64>,
+    force_after_days: Option<i64>,
+) -> Result<(), String> {
+    let mut databases = state.databases.write()
+        .map_err(|e| {
+            eprintln!("set_master_key_policy: Lock poisoned: {}", e);
+            "Failed to access database state".to_string()
+        })?;
+
+    if let Some(db) = databases.get_mut(&database_id) {
+        db.set_master_key_policy(recommend_after_days, force_after_days)
+            .map_err(|e| e.to_string())?;
+        emit_db_changed(&app, &database_id, db, ChangeKind::SettingsChanged, Vec::new());
+        Ok(())
+    } else {
+        Err("No database loaded".to_string())
+    }
+}
+
+#[tauri::command]
+pub async fn change_master_key(
+    app: AppHandle,
+    database_id: String,
+    new_password: String,
+) -> Result<(), String> {
+    // Re-encrypting with the new key runs the KDF; keep it off the main thread
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app.state::<AppState>();
+        // Changing the key saves immediately, so it has to serialize with autosave
+        let _save_guard = state.save_lock.lock()
+            .map_err(|e| {
+                eprintln!("change_master_key: Save lock poisoned: {}", e);
+                "Failed to access database state".to_string()
+            })?;
+
+        let pending = {
+            let databases = state.databases.read()
+                .map_err(|e| {
+                    eprintln!("change_master_key: Lock poisoned: {}", e);
+                    "Failed to access database state".to_string()
+                })?;
+            let db = databases.get(&database_id).ok_or("No database loaded".to_string())?;
+            db.prepare_key_change(new_password).map_err(|e| e.to_string())?
+        };
+
+        // The session keeps the old key until the file is written, so a failed write changes nothing
+        pending.write().map_err(|e| e.to_string())?;
+
+        let mut databases = state.databases.write()
+            .map_err(|e| {
+                eprintln!("change_master_key: Lock poisoned: {}", e);
+                "Failed to access database state".to_string()
+            })?;
+        let db = databases.get_mut(&database_id).ok_or("No database loaded".to_string())?;
+        db.finish_key_change(pending);
+        emit_db_changed(&app, &database_id, db, ChangeKind::Saved, Vec::new());
+        Ok(())
+    })
+    .await
+    .map_err(|e| format!("Failed to change master key: {}", e))?
+}
@@ -1,30 +1,31 @@
-use crate::kdbx::EntryData;
+use crate::events::{emit_db_changed, ChangeKind};
+use crate::kdbx::{AccessAction, EntryData};
 use crate::state::AppState;
-use tauri::State;
+use tauri::{AppHandle, State};
 
 #[tauri::command]
-pub fn get_entries(state: State<AppState>, group_uuid: String) -> Result<Vec<EntryData>, String> {
-    let database_lock = state.database.lock()
+pub fn get_entries(state: State<AppState>, database_id: String, group_uuid: String) -> Result<Vec<EntryData>, String> {
+    let databases = state.databases.read()
         .map_err(|e| {
             eprintln!("get_entries: Lock poisoned: {}", e);
             "Failed to access database state".to_string()
         })?;
-    let db = database_lock
-        .as_ref()
+    let db = databases
+        .get(&database_id)
         .ok_or("Database not loaded".to_string())?;
 
     db.get_entries_in_group(&group_uuid).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub fn get_favorite_entries(state: State<AppState>) -> Result<Vec<EntryData>, String> {
-    let database_lock = state.database.lock()
+pub fn get_favorite_entries(state: State<AppState>, database_id: String) -> Result<Vec<EntryData>, String> {
+    let databases = state.databases.read()
         .map_err(|e| {
             eprintln!("get_favorite_entries: Lock poisoned: {}", e);
             "Failed to access database state".to_string()
         })?;
-    let db = database_lock
-        .as_ref()
+    let db = databases
+        .get(&database_id)
         .ok_or("Database not loaded".to_string())?;
 
     let all_entries = db.get_all_entries();
@@ -37,14 +38,14 @@ pub fn get_favorite_entries(state: State<AppState>) -> Result<Vec<EntryData>, St
 }
 
 #[tauri::command]
-pub fn get_entry(state: State<AppState>, entry_uuid: String) -> Result<EntryData, String> {
-    let database_lock = state.database.lock()
+pub fn get_entry(state: State<AppState>, database_id: String, entry_uuid: String) -> Result<EntryData, String> {
+    let databases = state.databases.read()
         .map_err(|e| {
             eprintln!("get_entry: Lock poisoned: {}", e);
             "Failed to access database state".to_string()
         })?;
 
-    if let Some(db) = database_lock.as_ref() {
+    if let Some(db) = databases.get(&database_id) {
         db.get_entry(&entry_uuid).map_err(|e| e.to_string())
     } else {
         Err("No database loaded".to_string())
@@ -52,15 +53,16 @@ pub fn get_entry(state: State<AppState>, entry_uuid: String) -> Result<EntryData
 }
 
 #[tauri::command]
-pub fn create_entry(state: State<AppState>, entry: EntryData) -> Result<(), String> {
-    let mut database_lock = state.database.lock()
+pub fn create_entry(app: AppHandle, state: State<AppState>, database_id: String, entry: EntryData) -> Result<(), String> {
+    let mut databases = state.databases.write()
         .map_err(|e| {
             eprintln!("create_entry: Lock poisoned: {}", e);
             "Failed to access database state".to_string()
         })?;
 
-    if let Some(db) = database_lock.as_mut() {
-        db.create_entry(entry).map_err(|e| e.to_string())?;
+    if let Some(db) = databases.get_mut(&database_id) {
+        let uuid = db.create_entry(entry).map_err(|e| e.to_string())?;
+        emit_db_changed(&app, &database_id, db, ChangeKind::EntryCreated, vec![uuid]);
         Ok(())
     } else {
         Err("No database loaded".to_string())
@@ -68,15 +70,17 @@ pub fn create_entry(state: State<AppState>, entry: EntryData) -> Result<(), Stri
 }
 
 #[tauri::command]
-pub fn update_entry(state: State<AppState>, entry: EntryData) -> Result<(), String> {
-    let mut database_lock = state.database.lock()
+pub fn update_entry(app: AppHandle, state: State<AppState>, database_id: String, entry: EntryData) -> Result<(), String> {
+    let mut databases = state.databases.write()
         .map_err(|e| {
             eprintln!("update_entry: Lock poisoned: {}", e);
             "Failed to access database state".to_string()
         })?;
 
-    if let Some(db) = database_lock.as_mut() {
+    if let Some(db) = databases.get_mut(&database_id) {
+        let uuid = entry.uuid.clone();
         db.update_entry(entry).map_err(|e| e.to_string())?;
+        emit_db_changed(&app, &database_id, db, ChangeKind::EntryUpdated, vec![uuid]);
         Ok(())
     } else {
         Err("No database loaded".to_string())
@@ -84,15 +88,16 @@ pub fn update_entry(state: State<AppState>, entry: EntryData) -> Result<(), Stri
 }
 
 #[tauri::command]
-pub fn delete_entry(state: State<AppState>, entry_uuid: String) -> Result<(), String> {
-    let mut database_lock = state.database.lock()
+pub fn delete_entry(app: AppHandle, state: State<AppState>, database_id: String, entry_uuid: String) -> Result<(), String> {
+    let mut databases = state.databases.write()
         .map_err(|e| {
             eprintln!("delete_entry: Lock poisoned: {}", e);
             "Failed to access database state".to_string()
         })?;
 
-    if let Some(db) = database_lock.as_mut() {
+    if let Some(db) = databases.get_mut(&database_id) {
         db.delete_entry(&entry_uuid).map_err(|e| e.to_string())?;
+        emit_db_changed(&app, &database_id, db, ChangeKind::EntryDeleted, vec![entry_uuid]);
         Ok(())
     } else {
         Err("No database loaded".to_string())
@@ -100,17 +105,64 @@ pub fn delete_entry(state: State<AppState>, entry_uuid: String) -> Result<(), St
 }
 
 #[tauri::command]
-pub fn move_entry(state: State<AppState>, entry_uuid: String, new_group_uuid: String) -> Result<(), String> {
-    let mut database_lock = state.database.lock()
+pub fn move_entry(app: AppHandle, state: State<AppState>, database_id: String, entry_uuid: String, new_group_uuid: String) -> Result<(), String> {
+    let mut databases = state.databases.write()
         .map_err(|e| {
             eprintln!("move_entry: Lock poisoned: {}", e);
             "Failed to access database state".to_string()
         })?;
 
-    if let Some(db) = database_lock.as_mut() {
+    if let Some(db) = databases.get_mut(&database_id) {
         db.move_entry(&entry_uuid, &new_group_uuid).map_err(|e| e.to_string())?;
+        emit_db_changed(&app, &database_id, db, ChangeKind::EntryMoved, vec![entry_uuid, new_group_uuid]);
         Ok(())
     } else {
         Err("No database loaded".to_string())
     }
 }
+
+#[tauri::command]
+pub fn record_entry_access(app: AppHandle, state: State<AppState>, database_id: String, entry_uuid: String, action: AccessAction) -> Result<(), String> {
+    let mut databases = state.databases.write()
+        .map_err(|e| {
+            eprintln!("record_entry_access: Lock poisoned: {}", e);
+            "Failed to access database state".to_string()
+        })?;
+
+    if let Some(db) = databases.get_mut(&database_id) {
+        if db.record_entry_access(&entry_uuid, action).map_err(|e| e.to_string())? {
+            emit_db_changed(&app, &database_id, db, ChangeKind::EntryAccessed, vec![entry_uuid]);
+        }
+        Ok(())
+    } else {
+        Err("No database loaded".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn get_most_used_entries(state: State<AppState>, database_id: String, limit: usize) -> Result<Vec<EntryData>, String> {
+    let databases = state.databases.read()
+        .map_err(|e| {
+            eprintln!("get_most_used_entries: Lock poisoned: {}", e);
+            "Failed to access database state".to_string()
+        })?;
+    let db = databases
+        .get(&database_id)
+        .ok_or("Database not loaded".to_string())?;
+
+    Ok(db.get_most_used_entries(limit))
+}
+
+#[tauri::command]
+pub fn get_recently_used_entries(state: State<AppState>, database_id: String, limit: usize) -> Result<Vec<EntryData>, String> {
+    let databases = state.databases.read()
+        .map_err(|e| {
+            eprintln!("get_recently_used_entries: Lock poisoned: {}", e);
+            "Failed to access database state".to_string()
+        })?;
+    let db = databases
+        .get(&database_id)
+        .ok_or("Database not loaded".to_string())?;
+
+    Ok(db.get_recently_used_entries(limit))
+}
@@ -1,22 +1,26 @@
+use crate::events::{emit_db_changed, ChangeKind};
 use crate::state::AppState;
-use tauri::State;
+use tauri::{AppHandle, State};
 
 #[tauri::command]
 pub fn create_group(
+    app: AppHandle,
     state: State<AppState>,
+    database_id: String,
     name: String,
     parent_uuid: Option<String>,
     icon_id: Option<u32>,
 ) -> Result<(), String> {
-    let mut database_lock = state.database.lock()
+    let mut databases = state.databases.write()
         .map_err(|e| {
             eprintln!("create_group: Lock poisoned: {}", e);
             "Failed to access database state".to_string()
         })?;
 
-    if let Some(db) = database_lock.as_mut() {
-        db.create_group(name, parent_uuid, icon_id)
+    if let Some(db) = databases.get_mut(&database_id) {
+        let uuid = db.create_group(name, parent_uuid, icon_id)
             .map_err(|e| e.to_string())?;
+        emit_db_changed(&app, &database_id, db, ChangeKind::GroupCreated, vec![uuid]);
         Ok(())
     } else {
         Err("No database loaded".to_string())
@@ -25,20 +29,23 @@ pub fn create_group(
 
 #[tauri::command]
 pub fn rename_group(
+    app: AppHandle,
     state: State<AppState>,
+    database_id: String,
     group_uuid: String,
     new_name: String,
     icon_id: Option<u32>,
 ) -> Result<(), String> {
-    let mut database_lock = state.database.lock()
+    let mut databases = state.databases.write()
         .map_err(|e| {
             eprintln!("rename_group: Lock poisoned: {}", e);
             "Failed to access database state".to_string()
         })?;
 
-    if let Some(db) = database_lock.as_mut() {
+    if let Some(db) = databases.get_mut(&database_id) {
         db.rename_group(&group_uuid, new_name, icon_id)
             .map_err(|e| e.to_string())?;
+        emit_db_changed(&app, &database_id, db, ChangeKind::GroupUpdated, vec![group_uuid]);
         Ok(())
     } else {
         Err("No database loaded".to_string())
@@ -47,19 +54,22 @@ pub fn rename_group(
 
 #[tauri::command]
 pub fn move_group(
+    app: AppHandle,
     state: State<AppState>,
+    database_id: String,
     group_uuid: String,
     new_parent_uuid: String,
 ) -> Result<(), String> {
-    let mut database_lock = state.database.lock()
+    let mut databases = state.databases.write()
         .map_err(|e| {
             eprintln!("move_group: Lock poisoned: {}", e);
             "Failed to access database state".to_string()
         })?;
 
-    if let Some(db) = database_lock.as_mut() {
+    if let Some(db) = databases.get_mut(&database_id) {
         db.move_group(&group_uuid, &new_parent_uuid)
             .map_err(|e| e.to_string())?;
+        emit_db_changed(&app, &database_id, db, ChangeKind::GroupMoved, vec![group_uuid, new_parent_uuid]);
         Ok(())
     } else {
         Err("No database loaded".to_string())
@@ -68,19 +78,22 @@ pub fn move_group(
 
 #[tauri::command]
 pub fn reorder_group(
+    app: AppHandle,
     state: State<AppState>,
+    database_id: String,
     group_uuid: String,
     target_index: usize,
 ) -> Result<(), String> {
-    let mut database_lock = state.database.lock()
+    let mut databases = state.databases.write()
         .map_err(|e| {
             eprintln!("reorder_group: Lock poisoned: {}", e);
             "Failed to access database state".to_string()
         })?;
 
-    if let Some(db) = database_lock.as_mut() {
+    if let Some(db) = databases.get_mut(&database_id) {
         db.reorder_group(&group_uuid, target_index)
             .map_err(|e| e.to_string())?;
+        emit_db_changed(&app, &database_id, db, ChangeKind::GroupMoved, vec![group_uuid]);
         Ok(())
     } else {
         Err("No database loaded".to_string())
@@ -88,15 +101,64 @@ pub fn reorder_group(
 }
 
 #[tauri::command]
-pub fn delete_group(state: State<AppState>, group_uuid: String) -> Result<(), String> {
-    let mut database_lock = state.database.lock()
+pub fn delete_group(app: AppHandle, state: State<AppState>, database_id: String, group_uuid: String) -> Result<(), String> {
+    let mut databases = state.databases.write()
         .map_err(|e| {
             eprintln!("delete_group: Lock poisoned: {}", e);
             "Failed to access database state".to_string()
         })?;
 
-    if let Some(db) = database_lock.as_mut() {
+    if let Some(db) = databases.get_mut(&database_id) {
         db.delete_group(&group_uuid).map_err(|e| e.to_string())?;
+        emit_db_changed(&app, &database_id, db, ChangeKind::GroupDeleted, vec![group_uuid]);
+        Ok(())
+    } else {
+        Err("No database loaded".to_string())
+    }
+}
+
+/// `enabled: None` makes the group inherit the setting from its parent
+#[tauri::command]
+pub fn set_group_searching(
+    app: AppHandle,
+    state: State<AppState>,
+    database_id: String,
+    group_uuid: String,
+    enabled: Option<bool>,
+) -> Result<(), String> {
+    let mut databases = state.databases.write()
+        .map_err(|e| {
+            eprintln!("set_group_searching: Lock poisoned: {}", e);
+            "Failed to access database state".to_string()
+        })?;
+
+    if let Some(db) = databases.get_mut(&database_id) {
+        db.set_group_searching(&group_uuid, enabled).map_err(|e| e.to_string())?;
+        emit_db_changed(&app, &database_id, db, ChangeKind::GroupUpdated, vec![group_uuid]);
+        Ok(())
+    } else {
+        Err("No database loaded".to_string())
+    }
+}
+
+/// `enabled: None` makes the group inherit the setting from its parent
+#[tauri::command]
+pub fn set_group_auto_type(
+    app: AppHandle,
+    state: State<AppState>,
+    database_id: String,
+    group_uuid: String,
+    enabled: Option<bool>,
+) -> Result<(), String> {
+    let mut databases = state.databases.write()
+        .map_err(|e| {
+            eprintln!("set_group_auto_type: Lock poisoned: {}", e);
+            "Failed to access database state".to_string()
+        })?;
+
+    if let Some(db) = databases.get_mut(&database_id) {
+        db.set_group_auto_type(&group_uuid, enabled).map_err(|e| e.to_string())?;
+        emit_db_changed(&app, &database_id, db, ChangeKind::GroupUpdated, vec![group_uuid]);
         Ok(())
     } else {
         Err("No database loaded".to_string())
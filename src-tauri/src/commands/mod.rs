@@ -1,6 +1,8 @@
+pub mod bulk;
 pub mod database;
 pub mod entry;
 pub mod group;
 pub mod password;
 pub mod search;
 pub mod security;
+pub mod tags;
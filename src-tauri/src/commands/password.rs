@@ -1,4 +1,7 @@
+use crate::kdbx::PasswordQuality;
+use crate::state::AppState;
 use rand::Rng;
+use tauri::State;
 
 #[tauri::command]
 pub fn generate_password(
@@ -42,3 +45,18 @@ pub fn generate_password(
 
     Ok(password)
 }
+
+#[tauri::command]
+pub fn password_quality(state: State<AppState>, database_id: String, entry_uuid: String) -> Result<PasswordQuality, String> {
+    let databases = state.databases.read()
+        .map_err(|e| {
+            eprintln!("password_quality: Lock poisoned: {}", e);
+            "Failed to access database state".to_string()
+        })?;
+
+    if let Some(db) = databases.get(&database_id) {
+        db.get_password_quality(&entry_uuid).map_err(|e| e.to_string())
+    } else {
+        Err("No database loaded".to_string())
+    }
+}
@@ -1,48 +1,222 @@
-use crate::kdbx::{DashboardStats, EntryData};
+use crate::events::{emit_db_changed, ChangeKind};
+use crate::kdbx::{DashboardStats, EntryData, SavedSearch, SearchOptions, SearchResult};
 use crate::state::AppState;
-use tauri::State;
+use serde::Serialize;
+use std::cmp::Reverse;
+use tauri::{AppHandle, State};
+
+/// A search hit from a cross-database search, tagged with the vault it came from.
+#[derive(Clone, Serialize)]
+pub struct DatabaseSearchResult {
+    pub database_id: String,
+    pub database_name: String,
+    #[serde(flatten)]
+    pub result: SearchResult,
+}
 
 #[tauri::command]
-pub fn search_entries(state: State<AppState>, query: String) -> Result<Vec<EntryData>, String> {
-    let database_lock = state.database.lock()
+pub fn search_entries(
+    state: State<AppState>,
+    database_id: String,
+    query: String,
+    options: Option<SearchOptions>,
+) -> Result<Vec<SearchResult>, String> {
+    let databases = state.databases.read()
         .map_err(|e| {
             eprintln!("search_entries: Lock poisoned: {}", e);
             "Failed to access database state".to_string()
         })?;
 
-    if let Some(db) = database_lock.as_ref() {
-        Ok(db.search_entries(&query))
+    if let Some(db) = databases.get(&database_id) {
+        db.search_entries(&query, &options.unwrap_or_default())
+            .map_err(|e| e.to_string())
     } else {
         Err("No database loaded".to_string())
     }
 }
 
 #[tauri::command]
-pub fn search_entries_in_group(state: State<AppState>, query: String, group_uuid: String) -> Result<Vec<EntryData>, String> {
-    let database_lock = state.database.lock()
+pub fn search_all_databases(
+    state: State<AppState>,
+    query: String,
+    database_ids: Option<Vec<String>>,
+    options: Option<SearchOptions>,
+) -> Result<Vec<DatabaseSearchResult>, String> {
+    let options = options.unwrap_or_default();
+    let databases = state.databases.read()
+        .map_err(|e| {
+            eprintln!("search_all_databases: Lock poisoned: {}", e);
+            "Failed to access database state".to_string()
+        })?;
+
+    // Stable vault order, so equally ranked results keep a consistent order across calls
+    let mut sources: Vec<_> = databases
+        .iter()
+        .filter(|(id, _)| match &database_ids {
+            Some(ids) => ids.contains(id),
+            None => true,
+        })
+        .collect();
+    sources.sort_by(|(_, a), (_, b)| a.path.cmp(&b.path));
+
+    let mut results = Vec::new();
+    for (id, db) in sources {
+        let database_name = db.get_metadata().name;
+        let found = db.search_entries(&query, &options).map_err(|e| e.to_string())?;
+        results.extend(found.into_iter().map(|result| DatabaseSearchResult {
+            database_id: id.clone(),
+            database_name: database_name.clone(),
+            result,
+        }));
+    }
+    // Each vault's results are ranked already; merge them into one ranking
+    results.sort_by_key(|r| Reverse(r.result.score));
+
+    Ok(results)
+}
+
+#[tauri::command]
+pub fn search_entries_in_group(
+    state: State<AppState>,
+    database_id: String,
+    query: String,
+    group_uuid: String,
+    options: Option<SearchOptions>,
+) -> Result<Vec<SearchResult>, String> {
+    let databases = state.databases.read()
         .map_err(|e| {
             eprintln!("search_entries_in_group: Lock poisoned: {}", e);
             "Failed to access database state".to_string()
         })?;
 
-    if let Some(db) = database_lock.as_ref() {
-        Ok(db.search_entries_in_group(&query, &group_uuid))
+    if let Some(db) = databases.get(&database_id) {
+        db.search_entries_in_group(&query, &group_uuid, &options.unwrap_or_default())
+            .map_err(|e| e.to_string())
     } else {
         Err("No database loaded".to_string())
     }
 }
 
 #[tauri::command]
-pub fn get_dashboard_stats(state: State<AppState>) -> Result<DashboardStats, String> {
-    let database_lock = state.database.lock()
+pub fn get_dashboard_stats(state: State<AppState>, database_id: String) -> Result<DashboardStats, String> {
+    let databases = state.databases.read()
         .map_err(|e| {
             eprintln!("get_dashboard_stats: Lock poisoned: {}", e);
             "Failed to access database state".to_string()
         })?;
     
-    if let Some(db) = database_lock.as_ref() {
+    if let Some(db) = databases.get(&database_id) {
         Ok(db.get_dashboard_stats())
     } else {
         Err("No database loaded".to_string())
     }
 }
+
+#[tauri::command]
+pub fn get_saved_searches(state: State<AppState>, database_id: String) -> Result<Vec<SavedSearch>, String> {
+    let databases = state.databases.read()
+        .map_err(|e| {
+            eprintln!("get_saved_searches: Lock poisoned: {}", e);
+            "Failed to access database state".to_string()
+        })?;
+
+    if let Some(db) = databases.get(&database_id) {
+        db.get_saved_searches().map_err(|e| e.to_string())
+    } else {
+        Err("No database loaded".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn create_saved_search(
+    app: AppHandle,
+    state: State<AppState>,
+    database_id: String,
+    name: String,
+    query: String,
+    options: Option<SearchOptions>,
+) -> Result<SavedSearch, String> {
+    let mut databases = state.databases.write()
+        .map_err(|e| {
+            eprintln!("create_saved_search: Lock poisoned: {}", e);
+            "Failed to access database state".to_string()
+        })?;
+
+    if let Some(db) = databases.get_mut(&database_id) {
+        let search = db
+            .create_saved_search(name, query, options.unwrap_or_default())
+            .map_err(|e| e.to_string())?;
+        emit_db_changed(&app, &database_id, db, ChangeKind::SavedSearchesChanged, vec![search.uuid.clone()]);
+        Ok(search)
+    } else {
+        Err("No database loaded".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn update_saved_search(
+    app: AppHandle,
+    state: State<AppState>,
+    database_id: String,
+    search_uuid: String,
+    name: String,
+    query: String,
+    options: Option<SearchOptions>,
+) -> Result<SavedSearch, String> {
+    let mut databases = state.databases.write()
+        .map_err(|e| {
+            eprintln!("update_saved_search: Lock poisoned: {}", e);
+            "Failed to access database state".to_string()
+        })?;
+
+    if let Some(db) = databases.get_mut(&database_id) {
+        let search = db
+            .update_saved_search(&search_uuid, name, query, options.unwrap_or_default())
+            .map_err(|e| e.to_string())?;
+        emit_db_changed(&app, &database_id, db, ChangeKind::SavedSearchesChanged, vec![search_uuid]);
+        Ok(search)
+    } else {
+        Err("No database loaded".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn delete_saved_search(
+    app: AppHandle,
+    state: State<AppState>,
+    database_id: String,
+    search_uuid: String,
+) -> Result<(), String> {
+    let mut databases = state.databases.write()
+        .map_err(|e| {
+            eprintln!("delete_saved_search: Lock poisoned: {}", e);
+            "Failed to access database state".to_string()
+        })?;
+
+    if let Some(db) = databases.get_mut(&database_id) {
+        db.delete_saved_search(&search_uuid).map_err(|e| e.to_string())?;
+        emit_db_changed(&app, &database_id, db, ChangeKind::SavedSearchesChanged, vec![search_uuid]);
+        Ok(())
+    } else {
+        Err("No database loaded".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn run_saved_search(
+    state: State<AppState>,
+    database_id: String,
+    search_uuid: String,
+) -> Result<Vec<EntryData>, String> {
+    let databases = state.databases.read()
+        .map_err(|e| {
+            eprintln!("run_saved_search: Lock poisoned: {}", e);
+            "Failed to access database state".to_string()
+        })?;
+
+    if let Some(db) = databases.get(&database_id) {
+        db.run_saved_search(&search_uuid).map_err(|e| e.to_string())
+    } else {
+        Err("No database loaded".to_string())
+    }
+}
@@ -14,19 +14,19 @@ pub struct BreachedEntry {
 type PrefixDelayTuple = (String, Vec<(String, String, String, String)>, u64);
 
 #[tauri::command]
-pub async fn check_breached_passwords(state: State<'_, AppState>) -> Result<Vec<BreachedEntry>, String> {
+pub async fn check_breached_passwords(state: State<'_, AppState>, database_id: String) -> Result<Vec<BreachedEntry>, String> {
     use std::collections::HashMap;
     use rand::Rng;
     
     // Extract all entries while holding the lock, then release it
     let all_entries = {
-        let database_lock = state.database.lock()
+        let databases = state.databases.read()
             .map_err(|e| {
                 eprintln!("check_breached_passwords: Lock poisoned: {}", e);
                 "Failed to access database state".to_string()
             })?;
         
-        if let Some(db) = database_lock.as_ref() {
+        if let Some(db) = databases.get(&database_id) {
             db.get_all_entries()
         } else {
             return Err("No database loaded".to_string());
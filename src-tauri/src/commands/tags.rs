@@ -0,0 +1,116 @@
+use crate::events::{emit_db_changed, ChangeKind};
+use crate::kdbx::{EntryData, TagCount};
+use crate::state::AppState;
+use tauri::{AppHandle, State};
+
+#[tauri::command]
+pub fn get_all_tags(state: State<AppState>, database_id: String) -> Result<Vec<TagCount>, String> {
+    let databases = state.databases.read()
+        .map_err(|e| {
+            eprintln!("get_all_tags: Lock poisoned: {}", e);
+            "Failed to access database state".to_string()
+        })?;
+
+    if let Some(db) = databases.get(&database_id) {
+        Ok(db.get_all_tags())
+    } else {
+        Err("No database loaded".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn get_entries_with_tags(
+    state: State<AppState>,
+    database_id: String,
+    tags: Vec<String>,
+    match_all: Option<bool>,
+) -> Result<Vec<EntryData>, String> {
+    let databases = state.databases.read()
+        .map_err(|e| {
+            eprintln!("get_entries_with_tags: Lock poisoned: {}", e);
+            "Failed to access database state".to_string()
+        })?;
+
+    if let Some(db) = databases.get(&database_id) {
+        Ok(db.get_entries_with_tags(&tags, match_all.unwrap_or(true)))
+    } else {
+        Err("No database loaded".to_string())
+    }
+}
+
+/// Returns the UUIDs of the entries that were retagged
+#[tauri::command]
+pub fn rename_tag(
+    app: AppHandle,
+    state: State<AppState>,
+    database_id: String,
+    old_name: String,
+    new_name: String,
+) -> Result<Vec<String>, String> {
+    let mut databases = state.databases.write()
+        .map_err(|e| {
+            eprintln!("rename_tag: Lock poisoned: {}", e);
+            "Failed to access database state".to_string()
+        })?;
+
+    if let Some(db) = databases.get_mut(&database_id) {
+        let uuids = db.rename_tag(&old_name, &new_name).map_err(|e| e.to_string())?;
+        if !uuids.is_empty() {
+            emit_db_changed(&app, &database_id, db, ChangeKind::EntryUpdated, uuids.clone());
+        }
+        Ok(uuids)
+    } else {
+        Err("No database loaded".to_string())
+    }
+}
+
+/// Returns the UUIDs of the entries that were retagged
+#[tauri::command]
+pub fn merge_tags(
+    app: AppHandle,
+    state: State<AppState>,
+    database_id: String,
+    source: String,
+    target: String,
+) -> Result<Vec<String>, String> {
+    let mut databases = state.databases.write()
+        .map_err(|e| {
+            eprintln!("merge_tags: Lock poisoned: {}", e);
+            "Failed to access database state".to_string()
+        })?;
+
+    if let Some(db) = databases.get_mut(&database_id) {
+        let uuids = db.merge_tags(&source, &target).map_err(|e| e.to_string())?;
+        if !uuids.is_empty() {
+            emit_db_changed(&app, &database_id, db, ChangeKind::EntryUpdated, uuids.clone());
+        }
+        Ok(uuids)
+    } else {
+        Err("No database loaded".to_string())
+    }
+}
+
+/// Returns the UUIDs of the entries the tag was removed from
+#[tauri::command]
+pub fn delete_tag(
+    app: AppHandle,
+    state: State<AppState>,
+    database_id: String,
+    name: String,
+) -> Result<Vec<String>, String> {
+    let mut databases = state.databases.write()
+        .map_err(|e| {
+            eprintln!("delete_tag: Lock poisoned: {}", e);
+            "Failed to access database state".to_string()
+        })?;
+
+    if let Some(db) = databases.get_mut(&database_id) {
+        let uuids = db.delete_tag(&name).map_err(|e| e.to_string())?;
+        if !uuids.is_empty() {
+            emit_db_changed(&app, &database_id, db, ChangeKind::EntryUpdated, uuids.clone());
+        }
+        Ok(uuids)
+    } else {
+        Err("No database loaded".to_string())
+    }
+}
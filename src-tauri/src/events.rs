@@ -0,0 +1,56 @@
+use crate::autosave;
+use crate::kdbx::Database;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+pub const DB_CHANGED_EVENT: &str = "db-changed";
+
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    EntryCreated,
+    EntryUpdated,
+    EntryDeleted,
+    EntryMoved,
+    EntryAccessed,
+    GroupCreated,
+    GroupUpdated,
+    GroupDeleted,
+    GroupMoved,
+    SettingsChanged,
+    SavedSearchesChanged,
+    Merged,
+    Undone,
+    Redone,
+    Saved,
+}
+
+#[derive(Clone, Serialize)]
+pub struct DatabaseChanged {
+    pub database_id: String,
+    pub kind: ChangeKind,
+    pub uuids: Vec<String>,
+    pub revision: u64,
+    pub dirty: bool,
+}
+
+// Broadcast to every webview window so multi-window editing stays consistent
+pub fn emit_db_changed(app: &AppHandle, database_id: &str, db: &Database, kind: ChangeKind, uuids: Vec<String>) {
+    let payload = DatabaseChanged {
+        database_id: database_id.to_string(),
+        kind,
+        uuids,
+        revision: db.revision(),
+        dirty: db.is_dirty(),
+    };
+
+    if let Err(e) = app.emit(DB_CHANGED_EVENT, payload) {
+        eprintln!("emit_db_changed: Failed to emit event: {}", e);
+    }
+
+    // Every change except a save itself may trigger an autosave. Access counters
+    // alone don't: they ride along with the next real save.
+    if !matches!(kind, ChangeKind::Saved | ChangeKind::EntryAccessed) {
+        autosave::schedule(app);
+    }
+}
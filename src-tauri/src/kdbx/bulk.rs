@@ -0,0 +1,311 @@
+use chrono::NaiveDateTime;
+use keepass::db::{Entry, History, Times, Value};
+use std::collections::HashSet;
+
+use super::database::Database;
+use super::error::DatabaseError;
+use super::tags::{format_tags, normalize_tags, parse_tags};
+use super::types::{BulkItemResult, BulkOperationResult};
+
+impl Database {
+    pub fn move_entries(&mut self, entry_uuids: &[String], new_group_uuid: &str) -> Result<BulkOperationResult, DatabaseError> {
+        // Target group must exist before anything is touched
+        let _ = self.find_group_by_uuid(new_group_uuid)?;
+
+        self.run_bulk("Move entries", entry_uuids, |db, uuid| db.move_entry(uuid, new_group_uuid))
+    }
+
+    pub fn delete_entries(&mut self, entry_uuids: &[String]) -> Result<BulkOperationResult, DatabaseError> {
+        self.run_bulk("Delete entries", entry_uuids, |db, uuid| db.delete_entry(uuid))
+    }
+
+    pub fn add_tags_to_entries(&mut self, entry_uuids: &[String], tags: &[String]) -> Result<BulkOperationResult, DatabaseError> {
+        let tags = normalize_tags(tags.iter().map(String::as_str));
+        self.run_bulk("Add tags", entry_uuids, |db, uuid| {
+            db.modify_entry_tags(uuid, |current| {
+                for tag in &tags {
+                    if !current.contains(tag) {
+                        current.push(tag.clone());
+                    }
+                }
+            })
+        })
+    }
+
+    pub fn remove_tags_from_entries(&mut self, entry_uuids: &[String], tags: &[String]) -> Result<BulkOperationResult, DatabaseError> {
+        self.run_bulk("Remove tags", entry_uuids, |db, uuid| {
+            db.modify_entry_tags(uuid, |current| {
+                current.retain(|t| !tags.iter().any(|tag| tag.trim() == t));
+            })
+        })
+    }
+
+    pub fn set_entries_favorite(&mut self, entry_uuids: &[String], is_favorite: bool) -> Result<BulkOperationResult, DatabaseError> {
+        self.run_bulk("Change favorites", entry_uuids, |db, uuid| {
+            let entry = db.find_entry_by_uuid_mut(uuid)?;
+            if is_favorite {
+                entry.fields.insert("_Favorite".to_string(), Value::Unprotected("true".to_string()));
+            } else {
+                entry.fields.remove("_Favorite");
+            }
+            entry.times.set_last_modification(Times::now());
+            Ok(())
+        })
+    }
+
+    pub fn set_entries_expiry(
+        &mut self,
+        entry_uuids: &[String],
+        expires: bool,
+        expiry_time: Option<String>,
+    ) -> Result<BulkOperationResult, DatabaseError> {
+        // Parse once up front so a malformed date rejects the whole batch
+        let expiry = match (expires, expiry_time.as_deref()) {
+            (true, Some(expiry_str)) if !expiry_str.is_empty() => Some(
+                Self::parse_expiry_input(expiry_str)
+                    .ok_or_else(|| DatabaseError::InvalidExpiry(expiry_str.to_string()))?,
+            ),
+            _ => None,
+        };
+
+        self.run_bulk("Change expiry", entry_uuids, |db, uuid| {
+            let entry = db.find_entry_by_uuid_mut(uuid)?;
+            entry.times.expires = expires;
+            if let Some(expiry) = expiry {
+                entry.times.set_expiry(expiry);
+            }
+            entry.times.set_last_modification(Times::now());
+            Ok(())
+        })
+    }
+
+    pub fn set_entries_icon(&mut self, entry_uuids: &[String], icon_id: Option<usize>) -> Result<BulkOperationResult, DatabaseError> {
+        self.run_bulk("Change icon", entry_uuids, |db, uuid| {
+            let entry = db.find_entry_by_uuid_mut(uuid)?;
+            entry.icon_id = icon_id;
+            entry.times.set_last_modification(Times::now());
+            Ok(())
+        })
+    }
+
+    /// Validates every UUID first, then applies `op` to each entry. If any item fails
+    /// `record_undo` restores the tree, so callers never see a half-applied batch.
+    /// A database that can't be changed at all fails the call rather than an item.
+    fn run_bulk<F>(&mut self, label: &str, entry_uuids: &[String], mut op: F) -> Result<BulkOperationResult, DatabaseError>
+    where
+        F: FnMut(&mut Self, &str) -> Result<(), DatabaseError>,
+    {
+        self.ensure_writable()?;
+
+        // Drop duplicates while keeping the caller's order
+        let mut seen = HashSet::new();
+        let uuids: Vec<&String> = entry_uuids.iter().filter(|u| seen.insert(u.as_str())).collect();
+
+        let mut results: Vec<BulkItemResult> = uuids
+            .iter()
+            .map(|uuid| match self.find_entry_by_uuid(uuid.as_str()) {
+                Ok(_) => BulkItemResult { uuid: uuid.to_string(), success: true, error: None },
+                Err(e) => BulkItemResult { uuid: uuid.to_string(), success: false, error: Some(e.to_string()) },
+            })
+            .collect();
+
+        if results.iter().any(|r| !r.success) {
+            return Ok(Self::abort_bulk(results));
+        }
+
+        // The whole batch is one undo step; a failure is rolled back and not recorded
+        let mut current_idx = 0;
+        let outcome = self.record_undo(label, |db| {
+            for (idx, uuid) in uuids.iter().enumerate() {
+                current_idx = idx;
+                op(db, uuid.as_str())?;
+            }
+            Ok(())
+        });
+
+        if let Err(e) = outcome {
+            results[current_idx].success = false;
+            results[current_idx].error = Some(e.to_string());
+            return Ok(Self::abort_bulk(results));
+        }
+
+        Ok(BulkOperationResult { applied: true, results })
+    }
+
+    fn abort_bulk(mut results: Vec<BulkItemResult>) -> BulkOperationResult {
+        for result in results.iter_mut().filter(|r| r.error.is_none()) {
+            result.success = false;
+            result.error = Some("Not applied: another item in the batch failed".to_string());
+        }
+        BulkOperationResult { applied: false, results }
+    }
+
+    fn modify_entry_tags<F>(&mut self, entry_uuid: &str, modify: F) -> Result<(), DatabaseError>
+    where
+        F: FnOnce(&mut Vec<String>),
+    {
+        let entry = self.find_entry_by_uuid_mut(entry_uuid)?;
+
+        let old_tags = entry.get("Tags").unwrap_or("").to_string();
+        let mut tags = parse_tags(&old_tags);
+        modify(&mut tags);
+        let new_tags = format_tags(&tags);
+
+        if new_tags == old_tags {
+            return Ok(());
+        }
+
+        // Tags are part of the history-tracked fields, same as in update_entry
+        Self::push_history_snapshot(entry);
+        entry.fields.insert("Tags".to_string(), Value::Unprotected(new_tags));
+        entry.times.set_last_modification(Times::now());
+        Ok(())
+    }
+
+    pub(super) fn push_history_snapshot(entry: &mut Entry) {
+        let mut history_entry = entry.clone();
+        history_entry.history = None;
+        if let Some(ref mut hist) = entry.history {
+            hist.add_entry(history_entry);
+        } else {
+            let mut new_history = History::default();
+            new_history.add_entry(history_entry);
+            entry.history = Some(new_history);
+        }
+    }
+
+    fn parse_expiry_input(expiry_str: &str) -> Option<NaiveDateTime> {
+        NaiveDateTime::parse_from_str(expiry_str, "%Y-%m-%dT%H:%M")
+            .or_else(|_| NaiveDateTime::parse_from_str(expiry_str, "%Y-%m-%dT%H:%M:%S"))
+            .ok()
+            // Subtract 1 hour to compensate for keepass-rs timezone conversion
+            .map(|expiry| expiry - chrono::Duration::hours(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use keepass::config::DatabaseConfig;
+    use keepass::db::Group;
+    use keepass::Database as KeepassDatabase;
+    use uuid::Uuid;
+
+    fn entry(title: &str) -> Entry {
+        let mut entry = Entry::new();
+        entry.fields.insert("Title".to_string(), Value::Unprotected(title.to_string()));
+        entry
+    }
+
+    // Root
+    // ├── a, b, c (entries)
+    // └── Work
+    fn fixture() -> (Database, Vec<String>, String) {
+        let mut db = KeepassDatabase::new(DatabaseConfig::default());
+        let entries = [entry("a"), entry("b"), entry("c")];
+        let uuids = entries.iter().map(|e| e.uuid.to_string()).collect();
+        for e in entries {
+            db.root.add_child(e);
+        }
+        let work = Group::new("Work");
+        let work_id = work.uuid.to_string();
+        db.root.add_child(work);
+        (Database::in_memory(db), uuids, work_id)
+    }
+
+    fn title(db: &Database, uuid: &str) -> String {
+        db.find_entry_by_uuid(uuid).unwrap().get_title().unwrap_or("").to_string()
+    }
+
+    #[test]
+    fn a_failing_item_rolls_back_the_whole_batch() {
+        let (mut db, uuids, work) = fixture();
+
+        let result = db
+            .run_bulk("Test", &uuids, |db, uuid| {
+                if uuid == uuids[1] {
+                    return Err(DatabaseError::EntryNotFound);
+                }
+                // Changes the tree's shape, so a stale index would point at the wrong node
+                db.move_entry(uuid, &work)
+            })
+            .unwrap();
+
+        assert!(!result.applied);
+        assert_eq!(result.results[1].error, Some(DatabaseError::EntryNotFound.to_string()));
+        for idx in [0, 2] {
+            assert!(!result.results[idx].success);
+            assert_eq!(
+                result.results[idx].error.as_deref(),
+                Some("Not applied: another item in the batch failed")
+            );
+        }
+
+        // Nothing moved, and the index agrees with the restored tree
+        assert_eq!(db.find_entry_group_uuid(&uuids[0]).unwrap(), db.db.root.uuid.to_string());
+        let a = Uuid::parse_str(&uuids[0]).unwrap();
+        assert_eq!(db.index.entry_parent(&db.db.root, &a).unwrap().uuid, db.db.root.uuid);
+        assert_eq!(title(&db, &uuids[0]), "a");
+
+        assert!(!db.is_dirty());
+        assert!(!db.get_undo_state().can_undo);
+    }
+
+    #[test]
+    fn unknown_uuids_are_reported_per_item_before_anything_runs() {
+        let (mut db, uuids, _) = fixture();
+        let missing = Uuid::new_v4().to_string();
+        let batch = vec![uuids[0].clone(), missing.clone(), uuids[0].clone()];
+
+        let mut calls = 0;
+        let result = db
+            .run_bulk("Test", &batch, |_, _| {
+                calls += 1;
+                Ok(())
+            })
+            .unwrap();
+
+        assert_eq!(calls, 0);
+        assert!(!result.applied);
+        // Duplicates are dropped
+        assert_eq!(result.results.len(), 2);
+        assert_eq!(result.results[1].uuid, missing);
+        assert_eq!(result.results[1].error, Some(DatabaseError::EntryNotFound.to_string()));
+        assert_eq!(
+            result.results[0].error.as_deref(),
+            Some("Not applied: another item in the batch failed")
+        );
+        assert!(!db.is_dirty());
+    }
+
+    #[test]
+    fn a_database_that_cannot_be_changed_fails_the_call() {
+        let (mut db, uuids, _) = fixture();
+        db.key_change_forced = true;
+
+        let result = db.set_entries_favorite(&uuids, true);
+
+        assert!(matches!(result, Err(DatabaseError::MasterKeyChangeRequired)));
+        assert!(!db.is_dirty());
+    }
+
+    #[test]
+    fn a_successful_batch_is_one_undo_step() {
+        let (mut db, uuids, work) = fixture();
+
+        let result = db.move_entries(&uuids, &work).unwrap();
+
+        assert!(result.applied);
+        assert!(result.results.iter().all(|r| r.success && r.error.is_none()));
+        for uuid in &uuids {
+            assert_eq!(db.find_entry_group_uuid(uuid).unwrap(), work);
+        }
+        assert_eq!(db.get_undo_state().undo_label.as_deref(), Some("Move entries"));
+
+        db.undo().unwrap();
+        for uuid in &uuids {
+            assert_eq!(db.find_entry_group_uuid(uuid).unwrap(), db.db.root.uuid.to_string());
+        }
+        assert!(!db.get_undo_state().can_undo);
+    }
+}
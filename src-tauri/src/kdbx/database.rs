@@ -1,27 +1,97 @@
 use argon2::Version as Argon2Version;
 use keepass::{
     config::{DatabaseConfig, KdfConfig},
+    db::Times,
     Database as KeepassDatabase, DatabaseKey,
 };
 use secrecy::{ExposeSecret, SecretString};
+use sha1::{Digest, Sha1};
 use std::fs::File;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 use std::collections::HashMap;
 
 use super::error::DatabaseError;
-use super::types::KdfInfo;
+use super::index::TreeIndex;
+use super::kdf::MIN_AES_ROUNDS;
+use super::lockfile::{LockFile, LockRefresher};
+use super::types::{DatabaseStatus, KdfInfo, KdfSettings, LockInfo};
+use super::undo::UndoJournal;
+
+/// A snapshot of the database taken for writing, see `Database::prepare_save`.
+pub struct PendingSave {
+    db: KeepassDatabase,
+    path: PathBuf,
+    password: SecretString,
+    revision: u64,
+    // Save As / Save a Copy hold the target's lock file while writing
+    lock: Option<LockFile>,
+    // The file gets a different master password than the session has
+    new_key: bool,
+}
+
+impl PendingSave {
+    /// Runs the KDF and writes the file. Takes seconds with strong Argon2
+    /// settings, so callers run it without holding the database lock.
+    pub fn write(&self) -> Result<(), DatabaseError> {
+        let key = DatabaseKey::new().with_password(self.password.expose_secret());
+
+        let file = File::create(&self.path)
+            .map_err(|e| DatabaseError::SaveError(format!("Failed to create file: {}", e)))?;
+
+        self.db
+            .save(&mut std::io::BufWriter::new(file), key)
+            .map_err(|e| DatabaseError::SaveError(e.to_string()))
+    }
+}
+
+/// `path` with symlinks, `.` and `..` resolved, so two spellings of the same file
+/// compare equal. A file that doesn't exist yet is resolved through its directory.
+fn canonical_path(path: &Path) -> PathBuf {
+    if let Ok(canonical) = path.canonicalize() {
+        return canonical;
+    }
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    match (dir.canonicalize(), path.file_name()) {
+        (Ok(dir), Some(name)) => dir.join(name),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// The decrypted file on disk, read for a merge. See `Database::read_disk_copy`.
+pub struct DiskCopy {
+    db: KeepassDatabase,
+    content_hash: String,
+    last_modified: Option<SystemTime>,
+}
 
 pub struct Database {
     pub db: KeepassDatabase,
     pub path: PathBuf,
     pub password: SecretString,
     pub last_modified: Option<SystemTime>,
+    // SHA-1 of the file contents as last read or written; mtimes alone are too coarse
+    content_hash: Option<String>,
+    pub(super) undo: UndoJournal,
+    // UUID lookups; rebuilt whenever nodes move, see reindex()
+    pub(super) index: TreeIndex,
+    // Bumped by every mutation; the database is dirty while it differs from saved_revision
+    revision: u64,
+    saved_revision: u64,
+    // Released (file removed) when the Database is dropped; None in read-only mode
+    lock: Option<LockFile>,
+    read_only: bool,
+    // Set when MasterKeyChangeForce has elapsed; blocks mutations until the key is changed
+    pub(super) key_change_forced: bool,
 }
 
 impl Database {
     pub fn create(path: PathBuf, password: String) -> Result<Self, DatabaseError> {
         let secret_password = SecretString::new(password);
+        let lock = LockFile::acquire(&path)?;
         
         // Extract database name from filename (without .kdbx extension)
         let db_name = path
@@ -43,14 +113,25 @@ impl Database {
         };
         
         let mut db = KeepassDatabase::new(config);
-        db.root.name = db_name;
+        db.root.name = db_name.clone();
+        db.meta.database_name = Some(db_name);
+        db.meta.master_key_changed = Some(Times::now());
         
         let mut new_db = Self {
             db,
             path: path.clone(),
             password: secret_password,
             last_modified: None,
+            content_hash: None,
+            undo: UndoJournal::default(),
+            index: TreeIndex::default(),
+            revision: 0,
+            saved_revision: 0,
+            lock: Some(lock),
+            read_only: false,
+            key_change_forced: false,
         };
+        new_db.reindex();
         
         new_db.save()?;
         new_db.last_modified = std::fs::metadata(&path)
@@ -60,15 +141,26 @@ impl Database {
         Ok(new_db)
     }
 
-    pub fn open(path: PathBuf, password: String) -> Result<Self, DatabaseError> {
+    pub fn open(path: PathBuf, password: String, read_only: bool) -> Result<Self, DatabaseError> {
         let secret_password = SecretString::new(password);
+
+        // Files we can't write to are always opened read-only
+        let read_only = read_only || !Self::is_writable(&path);
+
+        // Check the lock before the expensive KDF; it is dropped again if opening fails.
+        // Read-only sessions never write, so they neither take nor respect the lock.
+        let lock = if read_only {
+            None
+        } else {
+            Some(LockFile::acquire(&path)?)
+        };
         
-        let file = File::open(&path)
+        let contents = std::fs::read(&path)
             .map_err(|e| DatabaseError::OpenError(format!("Failed to open file: {}", e)))?;
 
         let key = DatabaseKey::new().with_password(secret_password.expose_secret());
         
-        let db = KeepassDatabase::open(&mut std::io::BufReader::new(file), key)
+        let db = KeepassDatabase::open(&mut std::io::Cursor::new(&contents), key)
             .map_err(|e| {
                 if e.to_string().contains("Invalid credentials") {
                     DatabaseError::InvalidCredentials
@@ -81,58 +173,313 @@ impl Database {
             .ok()
             .and_then(|m| m.modified().ok());
 
-        Ok(Self {
+        let mut database = Self {
             db,
             path,
             password: secret_password,
             last_modified,
-        })
+            content_hash: Some(Self::hash_bytes(&contents)),
+            undo: UndoJournal::default(),
+            index: TreeIndex::default(),
+            revision: 0,
+            saved_revision: 0,
+            lock,
+            read_only,
+            key_change_forced: false,
+        };
+        database.key_change_forced = database.get_master_key_status().change_forced;
+        database.reindex();
+
+        Ok(database)
+    }
+
+    fn is_writable(path: &Path) -> bool {
+        // Opening for write without truncating is the only check that also covers ACLs and read-only mounts
+        std::fs::OpenOptions::new().write(true).open(path).is_ok()
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Whether this database lives in the file at `path`, however it is spelled
+    pub fn is_at(&self, path: &Path) -> bool {
+        canonical_path(&self.path) == canonical_path(path)
+    }
+
+    /// The abandoned lock of another session that opening this one took over
+    pub fn replaced_lock(&self) -> Option<LockInfo> {
+        self.lock.as_ref().and_then(|lock| lock.replaced()).cloned()
+    }
+
+    /// For keeping the lock file fresh while the vault stays open without being saved
+    pub fn lock_refresher(&self) -> Option<LockRefresher> {
+        self.lock.as_ref().map(LockFile::refresher)
+    }
+
+    pub(super) fn ensure_writable(&self) -> Result<(), DatabaseError> {
+        if self.read_only {
+            Err(DatabaseError::ReadOnly)
+        } else if self.key_change_forced {
+            Err(DatabaseError::MasterKeyChangeRequired)
+        } else {
+            Ok(())
+        }
     }
 
     pub fn save(&mut self) -> Result<(), DatabaseError> {
-        let key = DatabaseKey::new().with_password(self.password.expose_secret());
-        
-        let file = File::create(&self.path)
-            .map_err(|e| DatabaseError::SaveError(format!("Failed to create file: {}", e)))?;
+        let pending = self.prepare_save()?;
+        pending.write()?;
+        self.finish_save(pending);
+        Ok(())
+    }
 
-        self.db
-            .save(&mut std::io::BufWriter::new(file), key)
-            .map_err(|e| DatabaseError::SaveError(e.to_string()))?;
+    /// First half of a save that doesn't hold on to the database during the KDF:
+    /// snapshot it here, call `PendingSave::write` with no lock held, then
+    /// `finish_save`. Edits made in between keep the database dirty.
+    pub fn prepare_save(&self) -> Result<PendingSave, DatabaseError> {
+        self.ensure_writable()?;
+        Ok(PendingSave {
+            db: self.db.clone(),
+            path: self.path.clone(),
+            password: SecretString::new(self.password.expose_secret().clone()),
+            revision: self.revision,
+            lock: None,
+            new_key: false,
+        })
+    }
+
+    pub fn finish_save(&mut self, pending: PendingSave) {
+        self.mark_saved(pending.revision);
+    }
+
+    /// Snapshot for replacing the master password. The file is written right away,
+    /// since the old key keeps protecting it until then; like `prepare_save`, write
+    /// it with no lock held and then call `finish_key_change`. Nothing changes if
+    /// the write fails.
+    pub fn prepare_key_change(&self, new_password: String) -> Result<PendingSave, DatabaseError> {
+        // Not ensure_writable(): this is how a forced key change gets unblocked
+        if self.read_only {
+            return Err(DatabaseError::ReadOnly);
+        }
+        if new_password.is_empty() {
+            return Err(DatabaseError::InvalidSettings("The master password can't be empty".to_string()));
+        }
+
+        let mut db = self.db.clone();
+        db.meta.master_key_changed = Some(Times::now());
+        Ok(PendingSave {
+            db,
+            path: self.path.clone(),
+            password: SecretString::new(new_password),
+            revision: self.revision,
+            lock: None,
+            new_key: true,
+        })
+    }
+
+    /// Switches the session to the key written by `prepare_key_change`
+    pub fn finish_key_change(&mut self, pending: PendingSave) {
+        self.adopt_key(&pending);
+        self.password = pending.password;
+        self.mark_saved(pending.revision);
+    }
+
+    /// Prepares writing the database to a new file, optionally with a new
+    /// password and KDF. Nothing changes here until `finish_save_as`.
+    pub fn prepare_save_as(
+        &self,
+        path: PathBuf,
+        password: Option<String>,
+        kdf: Option<KdfSettings>,
+    ) -> Result<PendingSave, DatabaseError> {
+        if self.is_at(&path) {
+            return Err(DatabaseError::SaveError("Save As needs a different file".to_string()));
+        }
+        self.prepare_target(path, password, kdf)
+    }
+
+    /// Switches to the file written by a `prepare_save_as` snapshot
+    pub fn finish_save_as(&mut self, pending: PendingSave) {
+        self.db.config.kdf_config = pending.db.config.kdf_config.clone();
+        self.adopt_key(&pending);
+        self.path = pending.path;
+        self.password = pending.password;
+        self.lock = pending.lock;
+        // A fresh copy we just wrote is always ours to edit
+        self.read_only = false;
+        self.mark_saved(pending.revision);
+    }
+
+    /// Snapshot for writing a copy of the database to another file without
+    /// changing the current path, key or KDF - e.g. to hand a vault to someone
+    /// with a different password. Writing it is all that's left to do.
+    pub fn prepare_copy(
+        &self,
+        path: PathBuf,
+        password: Option<String>,
+        kdf: Option<KdfSettings>,
+    ) -> Result<PendingSave, DatabaseError> {
+        if self.is_at(&path) {
+            return Err(DatabaseError::SaveError("A copy can't overwrite the open database".to_string()));
+        }
+        self.prepare_target(path, password, kdf)
+    }
 
+    fn prepare_target(
+        &self,
+        path: PathBuf,
+        password: Option<String>,
+        kdf: Option<KdfSettings>,
+    ) -> Result<PendingSave, DatabaseError> {
+        let kdf_config = kdf.map(|k| k.to_config()).transpose()?;
+        // Held at least while writing so we never clobber a vault someone has open
+        let lock = LockFile::acquire(&path)?;
+        let new_key = password.is_some();
+        let password = password
+            .map(SecretString::new)
+            .unwrap_or_else(|| SecretString::new(self.password.expose_secret().clone()));
+
+        let mut db = self.db.clone();
+        if let Some(kdf_config) = kdf_config {
+            db.config.kdf_config = kdf_config;
+        }
+        if new_key {
+            db.meta.master_key_changed = Some(Times::now());
+        }
+
+        Ok(PendingSave {
+            db,
+            path,
+            password,
+            revision: self.revision,
+            lock: Some(lock),
+            new_key,
+        })
+    }
+
+    // A file written with a new password is a master key change: record when,
+    // and lift a forced-change block
+    fn adopt_key(&mut self, pending: &PendingSave) {
+        if pending.new_key {
+            self.db.meta.master_key_changed = pending.db.meta.master_key_changed;
+            self.key_change_forced = false;
+        }
+    }
+
+    // Bookkeeping after the current file has been written successfully
+    fn mark_saved(&mut self, revision: u64) {
         self.last_modified = std::fs::metadata(&self.path)
             .ok()
             .and_then(|m| m.modified().ok());
+        self.content_hash = Self::hash_file(&self.path);
 
-        Ok(())
+        self.saved_revision = revision;
+        if let Some(lock) = self.lock.as_ref() {
+            lock.refresh();
+        }
+        // Edits made while the file was being written are not in it; keep their undo steps
+        if !self.undo.keep_across_saves() && revision == self.revision {
+            self.undo.clear();
+        }
+    }
+
+    pub(super) fn reindex(&mut self) {
+        self.index = TreeIndex::build(&self.db.root);
+    }
+
+    pub(super) fn mark_dirty(&mut self) {
+        self.revision += 1;
+    }
+
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.revision != self.saved_revision
+    }
+
+    pub fn get_status(&self) -> DatabaseStatus {
+        DatabaseStatus {
+            revision: self.revision,
+            dirty: self.is_dirty(),
+            read_only: self.read_only,
+        }
     }
 
     pub fn check_for_changes(&self) -> Result<bool, DatabaseError> {
-        let current_modified = std::fs::metadata(&self.path)
-            .ok()
-            .and_then(|m| m.modified().ok());
+        Ok(self.has_external_changes())
+    }
 
-        Ok(match (self.last_modified, current_modified) {
-            (Some(last), Some(current)) => current > last,
+    /// True when the file on disk differs in content from what we last read or
+    /// wrote. A touch or a rewrite with identical bytes is not a change.
+    pub fn has_external_changes(&self) -> bool {
+        match (&self.content_hash, Self::hash_file(&self.path)) {
+            (Some(known), Some(current)) => *known != current,
             _ => false,
-        })
+        }
+    }
+
+    fn hash_file(path: &Path) -> Option<String> {
+        std::fs::read(path).ok().map(|contents| Self::hash_bytes(&contents))
     }
 
-    pub fn merge_database(&mut self) -> Result<(), DatabaseError> {
-        let file = File::open(&self.path)
+    fn hash_bytes(contents: &[u8]) -> String {
+        let mut hasher = Sha1::new();
+        hasher.update(contents);
+        format!("{:X}", hasher.finalize())
+    }
+
+    /// Decrypts the file as it is on disk now. This is the expensive part of a
+    /// merge, so it takes the path and key rather than the open database.
+    pub fn read_disk_copy(path: &Path, password: &SecretString) -> Result<DiskCopy, DatabaseError> {
+        let contents = std::fs::read(path)
             .map_err(|e| DatabaseError::OpenError(format!("Failed to open file: {}", e)))?;
 
-        let key = DatabaseKey::new().with_password(self.password.expose_secret());
-        
-        let disk_db = KeepassDatabase::open(&mut std::io::BufReader::new(file), key)
+        let key = DatabaseKey::new().with_password(password.expose_secret());
+
+        let db = KeepassDatabase::open(&mut std::io::Cursor::new(&contents), key)
             .map_err(|e| DatabaseError::OpenError(e.to_string()))?;
 
-        // Merge disk_db.root children into self.db.root
-        Self::merge_group_children(&disk_db.root, &mut self.db.root);
-        
-        self.last_modified = std::fs::metadata(&self.path)
-            .ok()
-            .and_then(|m| m.modified().ok());
+        Ok(DiskCopy {
+            db,
+            content_hash: Self::hash_bytes(&contents),
+            last_modified: std::fs::metadata(path)
+                .ok()
+                .and_then(|m| m.modified().ok()),
+        })
+    }
+
+    pub fn merge_disk_copy(&mut self, disk_copy: DiskCopy) -> Result<(), DatabaseError> {
+        self.record_undo("Merge changes from disk", |db| {
+            // Merge disk_copy.db.root children into self.db.root
+            Self::merge_group_children(&disk_copy.db.root, &mut db.db.root);
+
+            db.last_modified = disk_copy.last_modified;
+            db.content_hash = Some(disk_copy.content_hash);
+
+            Ok(())
+        })
+    }
 
+    /// Takes over the file as it is on disk, for a session with no unsaved changes.
+    /// Unlike a merge this keeps deletions and moves made by the other writer, and
+    /// it isn't an edit: nothing is left to save and the undo journal is untouched.
+    pub fn reload_disk_copy(&mut self, disk_copy: DiskCopy) -> Result<(), DatabaseError> {
+        if self.is_dirty() {
+            return Err(DatabaseError::SaveError(
+                "The database has unsaved changes; merge instead of reloading".to_string(),
+            ));
+        }
+
+        self.db = disk_copy.db;
+        self.reindex();
+        self.last_modified = disk_copy.last_modified;
+        self.content_hash = Some(disk_copy.content_hash);
+        // A new revision tells listeners to refresh; it matches the file, so it's clean
+        self.mark_dirty();
+        self.saved_revision = self.revision;
         Ok(())
     }
 
@@ -196,44 +543,79 @@ impl Database {
         match &self.db.config.kdf_config {
             KdfConfig::Aes { rounds } => KdfInfo {
                 kdf_type: "AES".to_string(),
-                is_weak: *rounds < 60000,
+                is_weak: *rounds < MIN_AES_ROUNDS,
                 iterations: Some(*rounds),
                 memory: None,
                 parallelism: None,
             },
-            KdfConfig::Argon2 { iterations, memory, parallelism, .. } => {
-                let memory_mb = memory / (1024 * 1024);
-                let is_weak = *iterations < 2 || memory_mb < 64 || *parallelism < 2;
-                KdfInfo {
-                    kdf_type: "Argon2d".to_string(),
-                    is_weak,
-                    iterations: Some(*iterations),
-                    memory: Some(*memory),
-                    parallelism: Some(*parallelism),
-                }
+            KdfConfig::Argon2 { iterations, memory, parallelism, .. } => KdfInfo {
+                kdf_type: "Argon2d".to_string(),
+                is_weak: Self::is_weak_argon2(*iterations, *memory),
+                iterations: Some(*iterations),
+                memory: Some(*memory),
+                parallelism: Some(*parallelism),
             },
-            KdfConfig::Argon2id { iterations, memory, parallelism, .. } => {
-                let memory_mb = memory / (1024 * 1024);
-                let is_weak = *iterations < 2 || memory_mb < 64 || *parallelism < 2;
-                KdfInfo {
-                    kdf_type: "Argon2id".to_string(),
-                    is_weak,
-                    iterations: Some(*iterations),
-                    memory: Some(*memory),
-                    parallelism: Some(*parallelism),
-                }
+            KdfConfig::Argon2id { iterations, memory, parallelism, .. } => KdfInfo {
+                kdf_type: "Argon2id".to_string(),
+                is_weak: Self::is_weak_argon2(*iterations, *memory),
+                iterations: Some(*iterations),
+                memory: Some(*memory),
+                parallelism: Some(*parallelism),
             },
         }
     }
 
+    // Attack cost scales with iterations x memory, so e.g. 1 iteration over 1 GiB
+    // is stronger than the 2 x 64 MiB baseline, not weaker. Lanes only speed up
+    // the legitimate unlock and don't add attacker cost.
+    fn is_weak_argon2(iterations: u64, memory: u64) -> bool {
+        let memory_mb = memory / (1024 * 1024);
+        iterations.saturating_mul(memory_mb) < 2 * 64
+    }
+
+    /// Switches to the recommended Argon2id parameters. Callers save afterwards;
+    /// the save runs the new KDF and shouldn't happen under the database lock.
     pub fn upgrade_kdf_parameters(&mut self) -> Result<(), DatabaseError> {
+        self.ensure_writable()?;
         self.db.config.kdf_config = KdfConfig::Argon2id {
             iterations: 2,
             memory: 64 * 1024 * 1024,
             parallelism: 2,
             version: Argon2Version::Version13,
         };
-        self.save()?;
+        self.mark_dirty();
+        Ok(())
+    }
+
+    /// Switches to a user-chosen KDF. Like the other database settings it takes
+    /// effect on the next save.
+    pub fn set_kdf_settings(&mut self, settings: KdfSettings) -> Result<(), DatabaseError> {
+        self.ensure_writable()?;
+        self.db.config.kdf_config = settings.to_config()?;
+        self.mark_dirty();
         Ok(())
     }
 }
+
+#[cfg(test)]
+impl Database {
+    /// A database that only lives in memory: no file, no lock file
+    pub(super) fn in_memory(db: KeepassDatabase) -> Self {
+        let mut database = Self {
+            db,
+            path: PathBuf::new(),
+            password: SecretString::new(String::new()),
+            last_modified: None,
+            content_hash: None,
+            undo: UndoJournal::default(),
+            index: TreeIndex::default(),
+            revision: 0,
+            saved_revision: 0,
+            lock: None,
+            read_only: false,
+            key_change_forced: false,
+        };
+        database.reindex();
+        database
+    }
+}
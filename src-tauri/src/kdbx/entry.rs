@@ -1,10 +1,15 @@
 use chrono::NaiveDateTime;
-use keepass::db::{Entry, Group, Node, Value, Times, History};
+use keepass::db::{Entry, Node, Value, Times, History};
 use uuid::Uuid;
 
 use super::database::Database;
 use super::error::DatabaseError;
-use super::types::{CustomField, EntryData, HistoryEntry};
+use super::tags::{format_tags, normalize_tags, parse_tags};
+use super::tree;
+use super::types::{AccessAction, CustomField, EntryData, HistoryEntry};
+
+/// Fields with a fixed meaning; everything else on an entry is a custom field
+pub(super) const STANDARD_FIELDS: [&str; 7] = ["Title", "UserName", "Password", "URL", "Notes", "Tags", "_Favorite"];
 
 impl Database {
     pub fn get_entries_in_group(&self, group_uuid: &str) -> Result<Vec<EntryData>, DatabaseError> {
@@ -22,23 +27,16 @@ impl Database {
         Ok(entries)
     }
 
+    /// Every entry search and stats should see, i.e. skipping groups with searching
+    /// disabled and the recycle bin
     pub fn get_all_entries(&self) -> Vec<EntryData> {
         let mut entries = Vec::new();
-        self.collect_entries(&self.db.root, &mut entries);
+        self.visit_searchable_entries(&mut |entry, group| {
+            entries.push(self.convert_entry(entry, &group.uuid.to_string()));
+        });
         entries
     }
 
-    pub(super) fn collect_entries(&self, group: &Group, entries: &mut Vec<EntryData>) {
-        let group_uuid = group.uuid.to_string();
-        
-        for node in &group.children {
-            match node {
-                Node::Entry(e) => entries.push(self.convert_entry(e, &group_uuid)),
-                Node::Group(g) => self.collect_entries(g, entries),
-            }
-        }
-    }
-
     pub(super) fn convert_entry(&self, entry: &Entry, group_uuid: &str) -> EntryData {
         let uuid = entry.uuid.to_string();
         let is_favorite = entry.get("_Favorite").unwrap_or("") == "true";
@@ -54,12 +52,9 @@ impl Database {
             adjusted.format("%Y-%m-%dT%H:%M").to_string()
         });
         
-        // Standard fields to exclude from custom fields
-        let standard_fields = ["Title", "UserName", "Password", "URL", "Notes", "Tags", "_Favorite"];
-        
         // Extract custom fields
         let custom_fields: Vec<CustomField> = entry.fields.iter()
-            .filter(|(key, _)| !standard_fields.contains(&key.as_str()))
+            .filter(|(key, _)| !STANDARD_FIELDS.contains(&key.as_str()))
             .map(|(key, value)| {
                 let (val, protected) = match value {
                     Value::Unprotected(s) => (s.clone(), false),
@@ -103,7 +98,7 @@ impl Database {
             password: entry.get_password().unwrap_or("").to_string(),
             url: entry.get("URL").unwrap_or("").to_string(),
             notes: entry.get("Notes").unwrap_or("").to_string(),
-            tags: entry.get("Tags").unwrap_or("").to_string(),
+            tags: parse_tags(entry.get("Tags").unwrap_or("")),
             group_uuid: group_uuid.to_string(),
             icon_id: entry.icon_id,
             is_favorite,
@@ -124,156 +119,65 @@ impl Database {
         Ok(self.convert_entry(entry, &group_uuid))
     }
 
-    pub fn create_entry(&mut self, entry_data: EntryData) -> Result<(), DatabaseError> {
-        let group = self.find_group_by_uuid_mut(&entry_data.group_uuid)?;
-        
-        // Use provided UUID or generate a new one
-        let uuid = if entry_data.uuid.is_empty() {
-            Uuid::new_v4()
-        } else {
-            Uuid::parse_str(&entry_data.uuid).map_err(|_| DatabaseError::InvalidUuid)?
-        };
-        
-        let mut entry = Entry {
-            uuid,
-            history: None,
-            ..Default::default()
-        };
+    pub fn create_entry(&mut self, entry_data: EntryData) -> Result<String, DatabaseError> {
+        self.record_undo("Create entry", |db| {
+            let group = db.find_group_by_uuid_mut(&entry_data.group_uuid)?;
         
-        // Set creation and modification timestamps
-        let now = Times::now();
-        entry.times.set_creation(now);
-        entry.times.set_last_modification(now);
-        entry.times.set_last_access(now);
-        
-        entry.fields.insert("Title".to_string(), Value::Unprotected(entry_data.title));
-        entry.fields.insert("UserName".to_string(), Value::Unprotected(entry_data.username));
-        entry.fields.insert("Password".to_string(), Value::Protected(entry_data.password.into()));
-        if !entry_data.url.is_empty() {
-            entry.fields.insert("URL".to_string(), Value::Unprotected(entry_data.url));
-        }
-        if !entry_data.notes.is_empty() {
-            entry.fields.insert("Notes".to_string(), Value::Unprotected(entry_data.notes));
-        }
-        if !entry_data.tags.is_empty() {
-            entry.fields.insert("Tags".to_string(), Value::Unprotected(entry_data.tags));
-        }
-        if entry_data.is_favorite {
-            entry.fields.insert("_Favorite".to_string(), Value::Unprotected("true".to_string()));
-        }
-        
-        // Add custom fields
-        for field in entry_data.custom_fields {
-            let value = if field.protected {
-                Value::Protected(field.value.into())
+            // Use provided UUID or generate a new one
+            let uuid = if entry_data.uuid.is_empty() {
+                Uuid::new_v4()
             } else {
-                Value::Unprotected(field.value)
+                Uuid::parse_str(&entry_data.uuid).map_err(|_| DatabaseError::InvalidUuid)?
             };
-            entry.fields.insert(field.name, value);
-        }
         
-        // Set icon ID if provided
-        if let Some(icon_id) = entry_data.icon_id {
-            entry.icon_id = Some(icon_id);
-        }
+            let mut entry = Entry {
+                uuid,
+                history: None,
+                ..Default::default()
+            };
         
-        // Set expiry settings
-        entry.times.expires = entry_data.expires;
-        if entry_data.expires {
-            if let Some(expiry_str) = entry_data.expiry_time {
-                if let Ok(mut expiry) = NaiveDateTime::parse_from_str(&expiry_str, "%Y-%m-%dT%H:%M") {
-                    // Subtract 1 hour to compensate for keepass-rs timezone conversion
-                    expiry -= chrono::Duration::hours(1);
-                    entry.times.set_expiry(expiry);
-                } else if let Ok(mut expiry) = NaiveDateTime::parse_from_str(&expiry_str, "%Y-%m-%dT%H:%M:%S") {
-                    // Subtract 1 hour to compensate for keepass-rs timezone conversion
-                    expiry -= chrono::Duration::hours(1);
-                    entry.times.set_expiry(expiry);
-                }
+            // Set creation and modification timestamps
+            let now = Times::now();
+            entry.times.set_creation(now);
+            entry.times.set_last_modification(now);
+            entry.times.set_last_access(now);
+        
+            entry.fields.insert("Title".to_string(), Value::Unprotected(entry_data.title));
+            entry.fields.insert("UserName".to_string(), Value::Unprotected(entry_data.username));
+            entry.fields.insert("Password".to_string(), Value::Protected(entry_data.password.into()));
+            if !entry_data.url.is_empty() {
+                entry.fields.insert("URL".to_string(), Value::Unprotected(entry_data.url));
             }
-        }
-        
-        group.add_child(entry);
-        Ok(())
-    }
-
-    pub fn update_entry(&mut self, entry_data: EntryData) -> Result<(), DatabaseError> {
-        let entry = self.find_entry_by_uuid_mut(&entry_data.uuid)?;
-        
-        // Check if any field has changed
-        let title_changed = entry.get_title().unwrap_or("") != entry_data.title;
-        let username_changed = entry.get_username().unwrap_or("") != entry_data.username;
-        let password_changed = entry.get_password().unwrap_or("") != entry_data.password;
-        let url_changed = entry.get("URL").unwrap_or("") != entry_data.url;
-        let notes_changed = entry.get("Notes").unwrap_or("") != entry_data.notes;
-        let tags_changed = entry.get("Tags").unwrap_or("") != entry_data.tags;
-        
-        let any_change = title_changed || username_changed || password_changed || 
-                        url_changed || notes_changed || tags_changed;
-        
-        if any_change {
-            // Clone the current entry state before updating
-            let mut history_entry = entry.clone();
-            // Remove history from the history entry to avoid nested history
-            history_entry.history = None;
-            // Set the history entry's modification time to the current entry's last modification time
-            if let Some(last_mod) = entry.times.get_last_modification() {
-                history_entry.times.set_last_modification(*last_mod);
+            if !entry_data.notes.is_empty() {
+                entry.fields.insert("Notes".to_string(), Value::Unprotected(entry_data.notes));
             }
-            // Add to history - initialize History if it doesn't exist
-            if let Some(ref mut hist) = entry.history {
-                hist.add_entry(history_entry);
-            } else {
-                let mut new_history = History::default();
-                new_history.add_entry(history_entry);
-                entry.history = Some(new_history);
+            let tags = normalize_tags(entry_data.tags.iter().map(String::as_str));
+            if !tags.is_empty() {
+                entry.fields.insert("Tags".to_string(), Value::Unprotected(format_tags(&tags)));
+            }
+            if entry_data.is_favorite {
+                entry.fields.insert("_Favorite".to_string(), Value::Unprotected("true".to_string()));
             }
-        }
-        
-        // Update modification and access timestamps
-        let now = Times::now();
-        entry.times.set_last_modification(now);
-        entry.times.set_last_access(now);
-        
-        // Standard fields to keep
-        let standard_fields = ["Title", "UserName", "Password", "URL", "Notes", "Tags", "_Favorite"];
-        
-        // Remove old custom fields (keep only standard fields)
-        entry.fields.retain(|key, _| standard_fields.contains(&key.as_str()));
-        
-        entry.fields.insert("Title".to_string(), Value::Unprotected(entry_data.title));
-        entry.fields.insert("UserName".to_string(), Value::Unprotected(entry_data.username));
-        entry.fields.insert("Password".to_string(), Value::Protected(entry_data.password.into()));
-        entry.fields.insert("URL".to_string(), Value::Unprotected(entry_data.url));
-        entry.fields.insert("Notes".to_string(), Value::Unprotected(entry_data.notes));
-        entry.fields.insert("Tags".to_string(), Value::Unprotected(entry_data.tags));
-        
-        // Update favorite status
-        if entry_data.is_favorite {
-            entry.fields.insert("_Favorite".to_string(), Value::Unprotected("true".to_string()));
-        } else {
-            entry.fields.remove("_Favorite");
-        }
         
-        // Add custom fields
-        for field in entry_data.custom_fields {
-            let value = if field.protected {
-                Value::Protected(field.value.into())
-            } else {
-                Value::Unprotected(field.value)
-            };
-            entry.fields.insert(field.name, value);
-        }
+            // Add custom fields
+            for field in entry_data.custom_fields {
+                let value = if field.protected {
+                    Value::Protected(field.value.into())
+                } else {
+                    Value::Unprotected(field.value)
+                };
+                entry.fields.insert(field.name, value);
+            }
         
-        // Update icon ID
-        entry.icon_id = entry_data.icon_id;
+            // Set icon ID if provided
+            if let Some(icon_id) = entry_data.icon_id {
+                entry.icon_id = Some(icon_id);
+            }
         
-        // Update expiry settings
-        entry.times.expires = entry_data.expires;
-        if entry_data.expires {
-            // If expires is checked, we need to set an expiry time
-            if let Some(expiry_str) = entry_data.expiry_time {
-                if !expiry_str.is_empty() {
+            // Set expiry settings
+            entry.times.expires = entry_data.expires;
+            if entry_data.expires {
+                if let Some(expiry_str) = entry_data.expiry_time {
                     if let Ok(mut expiry) = NaiveDateTime::parse_from_str(&expiry_str, "%Y-%m-%dT%H:%M") {
                         // Subtract 1 hour to compensate for keepass-rs timezone conversion
                         expiry -= chrono::Duration::hours(1);
@@ -285,129 +189,226 @@ impl Database {
                     }
                 }
             }
-        }
         
-        Ok(())
+            group.add_child(entry);
+            Ok(uuid.to_string())
+        })
     }
 
-    pub fn delete_entry(&mut self, entry_uuid: &str) -> Result<(), DatabaseError> {
-        let group_uuid = self.find_entry_group_uuid(entry_uuid)?;
-        let group = self.find_group_by_uuid_mut(&group_uuid)?;
+    pub fn update_entry(&mut self, entry_data: EntryData) -> Result<(), DatabaseError> {
+        self.record_undo("Edit entry", |db| {
+            let entry = db.find_entry_by_uuid_mut(&entry_data.uuid)?;
+        
+            // Check if any field has changed
+            let title_changed = entry.get_title().unwrap_or("") != entry_data.title;
+            let username_changed = entry.get_username().unwrap_or("") != entry_data.username;
+            let password_changed = entry.get_password().unwrap_or("") != entry_data.password;
+            let url_changed = entry.get("URL").unwrap_or("") != entry_data.url;
+            let notes_changed = entry.get("Notes").unwrap_or("") != entry_data.notes;
+            // Compared as sets of tags, so rewriting `a, b` as `a;b` alone isn't a change
+            let tags = normalize_tags(entry_data.tags.iter().map(String::as_str));
+            let tags_changed = parse_tags(entry.get("Tags").unwrap_or("")) != tags;
+        
+            let any_change = title_changed || username_changed || password_changed || 
+                            url_changed || notes_changed || tags_changed;
+        
+            if any_change {
+                // Clone the current entry state before updating
+                let mut history_entry = entry.clone();
+                // Remove history from the history entry to avoid nested history
+                history_entry.history = None;
+                // Set the history entry's modification time to the current entry's last modification time
+                if let Some(last_mod) = entry.times.get_last_modification() {
+                    history_entry.times.set_last_modification(*last_mod);
+                }
+                // Add to history - initialize History if it doesn't exist
+                if let Some(ref mut hist) = entry.history {
+                    hist.add_entry(history_entry);
+                } else {
+                    let mut new_history = History::default();
+                    new_history.add_entry(history_entry);
+                    entry.history = Some(new_history);
+                }
+            }
+        
+            // Update modification and access timestamps
+            let now = Times::now();
+            entry.times.set_last_modification(now);
+            entry.times.set_last_access(now);
         
-        let uuid = Uuid::parse_str(entry_uuid).map_err(|_| DatabaseError::EntryNotFound)?;
-        group.children.retain(|node| {
-            if let Node::Entry(e) = node {
-                e.uuid != uuid
+            // Remove old custom fields (keep only standard fields)
+            entry.fields.retain(|key, _| STANDARD_FIELDS.contains(&key.as_str()));
+        
+            entry.fields.insert("Title".to_string(), Value::Unprotected(entry_data.title));
+            entry.fields.insert("UserName".to_string(), Value::Unprotected(entry_data.username));
+            entry.fields.insert("Password".to_string(), Value::Protected(entry_data.password.into()));
+            entry.fields.insert("URL".to_string(), Value::Unprotected(entry_data.url));
+            entry.fields.insert("Notes".to_string(), Value::Unprotected(entry_data.notes));
+            entry.fields.insert("Tags".to_string(), Value::Unprotected(format_tags(&tags)));
+        
+            // Update favorite status
+            if entry_data.is_favorite {
+                entry.fields.insert("_Favorite".to_string(), Value::Unprotected("true".to_string()));
             } else {
-                true
+                entry.fields.remove("_Favorite");
+            }
+        
+            // Add custom fields
+            for field in entry_data.custom_fields {
+                let value = if field.protected {
+                    Value::Protected(field.value.into())
+                } else {
+                    Value::Unprotected(field.value)
+                };
+                entry.fields.insert(field.name, value);
+            }
+        
+            // Update icon ID
+            entry.icon_id = entry_data.icon_id;
+        
+            // Update expiry settings
+            entry.times.expires = entry_data.expires;
+            if entry_data.expires {
+                // If expires is checked, we need to set an expiry time
+                if let Some(expiry_str) = entry_data.expiry_time {
+                    if !expiry_str.is_empty() {
+                        if let Ok(mut expiry) = NaiveDateTime::parse_from_str(&expiry_str, "%Y-%m-%dT%H:%M") {
+                            // Subtract 1 hour to compensate for keepass-rs timezone conversion
+                            expiry -= chrono::Duration::hours(1);
+                            entry.times.set_expiry(expiry);
+                        } else if let Ok(mut expiry) = NaiveDateTime::parse_from_str(&expiry_str, "%Y-%m-%dT%H:%M:%S") {
+                            // Subtract 1 hour to compensate for keepass-rs timezone conversion
+                            expiry -= chrono::Duration::hours(1);
+                            entry.times.set_expiry(expiry);
+                        }
+                    }
+                }
             }
-        });
         
-        Ok(())
+            Ok(())
+        })
+    }
+
+    pub fn delete_entry(&mut self, entry_uuid: &str) -> Result<(), DatabaseError> {
+        self.record_undo("Delete entry", |db| {
+            let uuid = Uuid::parse_str(entry_uuid).map_err(|_| DatabaseError::EntryNotFound)?;
+            tree::remove_entry(&mut db.db.root, &uuid).ok_or(DatabaseError::EntryNotFound)?;
+            Ok(())
+        })
     }
 
     pub fn move_entry(&mut self, entry_uuid: &str, new_group_uuid: &str) -> Result<(), DatabaseError> {
-        // Find the current group containing the entry
-        let current_group_uuid = self.find_entry_group_uuid(entry_uuid)?;
+        self.record_undo("Move entry", |db| {
+            // Find the current group containing the entry
+            let current_group_uuid = db.find_entry_group_uuid(entry_uuid)?;
         
-        // If already in the target group, do nothing
-        if current_group_uuid == new_group_uuid {
-            return Ok(());
-        }
+            // If already in the target group, do nothing
+            if current_group_uuid == new_group_uuid {
+                return Ok(());
+            }
         
-        // Verify target group exists
-        let _ = self.find_group_by_uuid(new_group_uuid)?;
+            // Verify target group exists before detaching anything
+            let _ = db.find_group_by_uuid(new_group_uuid)?;
         
-        let uuid = Uuid::parse_str(entry_uuid).map_err(|_| DatabaseError::EntryNotFound)?;
+            let uuid = Uuid::parse_str(entry_uuid).map_err(|_| DatabaseError::EntryNotFound)?;
+            let entry_to_move = tree::remove_entry(&mut db.db.root, &uuid).ok_or(DatabaseError::EntryNotFound)?;
         
-        // Remove entry from current group and obtain it
-        let entry_to_move: Entry = {
-            let current_group = self.find_group_by_uuid_mut(&current_group_uuid)?;
+            // Add entry to new group
+            let new_group = db.find_group_by_uuid_mut(new_group_uuid)?;
+            new_group.add_child(entry_to_move);
+            Ok(())
+        })
+    }
 
-            // Find the position of the entry within the current group's children
-            let index = current_group
-                .children
-                .iter()
-                .position(|node| matches!(node, Node::Entry(e) if e.uuid == uuid))
-                .ok_or(DatabaseError::EntryNotFound)?;
+    /// Counts a use of the entry. Best-effort: a session that can't be written to
+    /// (read-only, or waiting on a forced key change) skips it and returns `false`
+    /// rather than failing the copy or open it came from.
+    pub fn record_entry_access(&mut self, entry_uuid: &str, action: AccessAction) -> Result<bool, DatabaseError> {
+        if self.ensure_writable().is_err() {
+            return Ok(false);
+        }
+        let entry = self.find_entry_by_uuid_mut(entry_uuid)?;
 
-            // Remove the node at the found index and extract the entry
-            match current_group.children.remove(index) {
-                Node::Entry(e) => e,
-                _ => unreachable!("Non-entry node found at entry position"),
+        // Every action currently counts as one use; matching here forces a decision for new actions
+        match action {
+            AccessAction::CopyPassword
+            | AccessAction::CopyUsername
+            | AccessAction::OpenUrl
+            | AccessAction::AutoType => {
+                // Only touch the access time and counter - no history snapshot, no modification time
+                entry.times.usage_count += 1;
+                entry.times.set_last_access(Times::now());
             }
-        };
-        
-        // Add entry to new group
-        let new_group = self.find_group_by_uuid_mut(new_group_uuid)?;
-        new_group.add_child(entry_to_move);
-        Ok(())
+        }
+
+        // Not an undoable edit, but the counters still need saving
+        self.mark_dirty();
+        Ok(true)
+    }
+
+    pub fn get_most_used_entries(&self, limit: usize) -> Vec<EntryData> {
+        let mut entries: Vec<EntryData> = self
+            .get_all_entries()
+            .into_iter()
+            .filter(|entry| entry.usage_count > 0)
+            .collect();
+
+        // Highest usage first, most recent access breaks ties
+        entries.sort_by(|a, b| {
+            b.usage_count
+                .cmp(&a.usage_count)
+                .then_with(|| b.last_accessed.cmp(&a.last_accessed))
+        });
+        entries.truncate(limit);
+        entries
+    }
+
+    pub fn get_recently_used_entries(&self, limit: usize) -> Vec<EntryData> {
+        let mut entries: Vec<EntryData> = self
+            .get_all_entries()
+            .into_iter()
+            .filter(|entry| entry.usage_count > 0 && entry.last_accessed.is_some())
+            .collect();
+
+        // Timestamps are formatted as %Y-%m-%dT%H:%M:%S, so string order is chronological
+        entries.sort_by(|a, b| b.last_accessed.cmp(&a.last_accessed));
+        entries.truncate(limit);
+        entries
     }
 
     pub(super) fn find_entry_by_uuid(&self, uuid: &str) -> Result<&Entry, DatabaseError> {
         let uuid_parsed = Uuid::parse_str(uuid).map_err(|_| DatabaseError::EntryNotFound)?;
-        self.find_entry_recursive(&self.db.root, &uuid_parsed)
+        // The tree walk only runs if the index is stale, i.e. inside a mutation
+        self.index
+            .entry(&self.db.root, &uuid_parsed)
+            .or_else(|| {
+                let path = tree::find_entry(&self.db.root, &uuid_parsed)?;
+                tree::entry_at(&self.db.root, &path)
+            })
             .ok_or(DatabaseError::EntryNotFound)
     }
 
     pub(super) fn find_entry_by_uuid_mut(&mut self, uuid: &str) -> Result<&mut Entry, DatabaseError> {
         let uuid_parsed = Uuid::parse_str(uuid).map_err(|_| DatabaseError::EntryNotFound)?;
-        let root_ptr = &mut self.db.root as *mut Group;
-        unsafe { Self::find_entry_recursive_mut_static(&mut *root_ptr, &uuid_parsed) }
-            .ok_or(DatabaseError::EntryNotFound)
-    }
-
-    fn find_entry_recursive<'a>(&'a self, group: &'a Group, uuid: &Uuid) -> Option<&'a Entry> {
-        for node in &group.children {
-            match node {
-                Node::Entry(e) if &e.uuid == uuid => return Some(e),
-                Node::Group(g) => {
-                    if let Some(found) = self.find_entry_recursive(g, uuid) {
-                        return Some(found);
-                    }
-                }
-                _ => {}
-            }
-        }
-        None
-    }
-
-    fn find_entry_recursive_mut_static<'a>(
-        group: &'a mut Group,
-        uuid: &Uuid,
-    ) -> Option<&'a mut Entry> {
-        for node in &mut group.children {
-            match node {
-                Node::Entry(e) if &e.uuid == uuid => return Some(e),
-                Node::Group(g) => {
-                    if let Some(found) = Self::find_entry_recursive_mut_static(g, uuid) {
-                        return Some(found);
-                    }
-                }
-                _ => {}
-            }
+        if self.index.entry(&self.db.root, &uuid_parsed).is_some() {
+            return self
+                .index
+                .entry_mut(&mut self.db.root, &uuid_parsed)
+                .ok_or(DatabaseError::EntryNotFound);
         }
-        None
+        let path = tree::find_entry(&self.db.root, &uuid_parsed).ok_or(DatabaseError::EntryNotFound)?;
+        tree::entry_at_mut(&mut self.db.root, &path).ok_or(DatabaseError::EntryNotFound)
     }
 
     pub(super) fn find_entry_group_uuid(&self, entry_uuid: &str) -> Result<String, DatabaseError> {
         let uuid_parsed = Uuid::parse_str(entry_uuid).map_err(|_| DatabaseError::EntryNotFound)?;
-        self.find_entry_group_uuid_recursive(&self.db.root, &uuid_parsed)
+        self.index
+            .entry_parent(&self.db.root, &uuid_parsed)
+            .or_else(|| {
+                let path = tree::find_entry(&self.db.root, &uuid_parsed)?;
+                tree::group_at(&self.db.root, &path.group)
+            })
+            .map(|group| group.uuid.to_string())
             .ok_or(DatabaseError::EntryNotFound)
     }
-
-    fn find_entry_group_uuid_recursive(&self, group: &Group, uuid: &Uuid) -> Option<String> {
-        for node in &group.children {
-            match node {
-                Node::Entry(e) if &e.uuid == uuid => return Some(group.uuid.to_string()),
-                Node::Group(g) => {
-                    if let Some(found) = self.find_entry_group_uuid_recursive(g, uuid) {
-                        return Some(found);
-                    }
-                }
-                _ => {}
-            }
-        }
-        None
-    }
 }
@@ -17,4 +17,30 @@ pub enum DatabaseError {
     GroupNotFound,
     #[error("Invalid UUID format")]
     InvalidUuid,
+    #[error("Invalid expiry time: {0}")]
+    InvalidExpiry(String),
+    #[error("Database is locked by {owner} on {host} since {since}")]
+    Locked { owner: String, host: String, since: String },
+    #[error("Database is open in read-only mode")]
+    ReadOnly,
+    #[error("The master key must be changed before the database can be modified")]
+    MasterKeyChangeRequired,
+    #[error("Lock file error: {0}")]
+    LockError(String),
+    #[error("Invalid database settings: {0}")]
+    InvalidSettings(String),
+    #[error("Invalid KDF settings: {0}")]
+    InvalidKdf(String),
+    #[error("Invalid search query: {0}")]
+    InvalidQuery(String),
+    #[error("Invalid regular expression '{pattern}': {message}")]
+    InvalidRegex { pattern: String, message: String },
+    #[error("Saved search not found")]
+    SavedSearchNotFound,
+    #[error("Invalid tag: {0}")]
+    InvalidTag(String),
+    #[error("Nothing to undo")]
+    NothingToUndo,
+    #[error("Nothing to redo")]
+    NothingToRedo,
 }
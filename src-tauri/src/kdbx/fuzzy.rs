@@ -0,0 +1,237 @@
+use std::ops::Range;
+
+/// How well a term matched a field, from best to worst
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MatchKind {
+    /// The whole field
+    Exact,
+    /// The start of the field
+    Prefix,
+    /// The start of a word inside the field
+    WordPrefix,
+    Substring,
+    /// A word this many edits away from the term
+    Typos(usize),
+}
+
+impl MatchKind {
+    pub fn points(self) -> u32 {
+        match self {
+            MatchKind::Exact => 10,
+            MatchKind::Prefix => 8,
+            MatchKind::WordPrefix => 6,
+            MatchKind::Substring => 4,
+            MatchKind::Typos(1) => 2,
+            MatchKind::Typos(_) => 1,
+        }
+    }
+}
+
+/// A match inside a field; `range` is in bytes of the original (unfolded) text
+#[derive(Clone, Debug)]
+pub struct Hit {
+    pub kind: MatchKind,
+    pub range: Range<usize>,
+}
+
+/// Lowercased text, remembering for every byte which char of the original it came
+/// from, since lowercasing can change the length of a char.
+struct Folded {
+    text: String,
+    origins: Vec<Range<usize>>,
+}
+
+impl Folded {
+    fn new(text: &str) -> Self {
+        let mut folded = String::with_capacity(text.len());
+        let mut origins = Vec::with_capacity(text.len());
+        for (start, c) in text.char_indices() {
+            let origin = start..start + c.len_utf8();
+            for lower in c.to_lowercase() {
+                folded.push(lower);
+                origins.extend(std::iter::repeat_n(origin.clone(), lower.len_utf8()));
+            }
+        }
+        Folded { text: folded, origins }
+    }
+
+    fn original(&self, range: Range<usize>) -> Range<usize> {
+        if range.is_empty() {
+            return 0..0;
+        }
+        self.origins[range.start].start..self.origins[range.end - 1].end
+    }
+}
+
+/// Lowercases a search term the same way `find` lowercases the text it searches
+pub fn fold_case(text: &str) -> String {
+    Folded::new(text).text
+}
+
+/// Classifies a match at `range` of `text` by where it sits
+pub fn classify(text: &str, range: &Range<usize>) -> MatchKind {
+    if range.start == 0 && range.end == text.len() {
+        MatchKind::Exact
+    } else if range.start == 0 {
+        MatchKind::Prefix
+    } else if text[..range.start].chars().next_back().is_some_and(|c| !c.is_alphanumeric()) {
+        MatchKind::WordPrefix
+    } else {
+        MatchKind::Substring
+    }
+}
+
+/// Finds `needle` (already passed through `fold_case`) in `text`, ignoring case.
+/// With `typos`, a needle of four or more characters also matches a word that is
+/// one edit away, or two from eight characters on.
+pub fn find(needle: &str, text: &str, typos: bool) -> Option<Hit> {
+    let folded = Folded::new(text);
+    if let Some(start) = folded.text.find(needle) {
+        let range = start..start + needle.len();
+        return Some(Hit {
+            kind: classify(&folded.text, &range),
+            range: folded.original(range),
+        });
+    }
+
+    // Phrases are matched as written
+    if !typos || needle.contains(char::is_whitespace) {
+        return None;
+    }
+    let needle: Vec<char> = needle.chars().collect();
+    let max_edits = match needle.len() {
+        0..=3 => return None,
+        4..=7 => 1,
+        _ => 2,
+    };
+
+    words(&folded.text)
+        .into_iter()
+        .filter_map(|range| {
+            let word: Vec<char> = folded.text[range.clone()].chars().collect();
+            // Comparing against the start of the word as well catches a typo in a word that isn't typed out yet
+            let start = &word[..word.len().min(needle.len())];
+            let edits = edit_distance(&needle, &word).min(edit_distance(&needle, start));
+            (edits <= max_edits).then_some((edits, range))
+        })
+        .min_by_key(|(edits, _)| *edits)
+        .map(|(edits, range)| Hit {
+            kind: MatchKind::Typos(edits),
+            range: folded.original(range),
+        })
+}
+
+/// Byte ranges of the alphanumeric runs in `text`
+fn words(text: &str) -> Vec<Range<usize>> {
+    let mut words = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices() {
+        match (c.is_alphanumeric(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                words.push(s..i);
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        words.push(s..text.len());
+    }
+    words
+}
+
+/// Optimal string alignment distance: insertions, deletions, substitutions and
+/// swaps of neighbouring characters each count as one edit.
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let substitution = rows[i - 1][j - 1] + usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = substitution.min(rows[i - 1][j] + 1).min(rows[i][j - 1] + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = distance;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(text: &str) -> Vec<char> {
+        text.chars().collect()
+    }
+
+    #[test]
+    fn classifies_by_position() {
+        let hit = |needle: &str, text: &str| find(&fold_case(needle), text, false).unwrap().kind;
+        assert_eq!(hit("bank", "Bank"), MatchKind::Exact);
+        assert_eq!(hit("bank", "Bank login"), MatchKind::Prefix);
+        assert_eq!(hit("login", "Bank login"), MatchKind::WordPrefix);
+        assert_eq!(hit("ogin", "Bank login"), MatchKind::Substring);
+    }
+
+    #[test]
+    fn ranges_map_back_through_case_folding() {
+        // 'İ' (2 bytes) lowercases to "i̇" (3 bytes), shifting everything after it
+        let hit = find(&fold_case("stan"), "İstanbul", false).unwrap();
+        assert_eq!(hit.range, 2..6);
+        assert_eq!(&"İstanbul"[hit.range], "stan");
+
+        // 'ẞ' (3 bytes) lowercases to 'ß' (2 bytes)
+        let text = "STRAẞE Nord";
+        let hit = find(&fold_case("nord"), text, false).unwrap();
+        assert_eq!(&text[hit.range], "Nord");
+
+        // A match covering the folded char covers all of it in the original
+        let hit = find(&fold_case("ẞe"), text, false).unwrap();
+        assert_eq!(&text[hit.range], "ẞE");
+
+        let hit = find(&fold_case("İs"), "İstanbul", false).unwrap();
+        assert_eq!(hit.kind, MatchKind::Prefix);
+        assert_eq!(hit.range, 0..3);
+    }
+
+    #[test]
+    fn typo_ranges_map_back_through_case_folding() {
+        let text = "İ Bnak";
+        let hit = find(&fold_case("bank"), text, true).unwrap();
+        assert_eq!(hit.kind, MatchKind::Typos(1));
+        assert_eq!(&text[hit.range], "Bnak");
+    }
+
+    #[test]
+    fn transpositions_are_one_edit() {
+        assert_eq!(edit_distance(&chars("abcd"), &chars("acbd")), 1);
+        assert_eq!(edit_distance(&chars("password"), &chars("passowrd")), 1);
+        // Optimal string alignment doesn't edit a substring twice
+        assert_eq!(edit_distance(&chars("ca"), &chars("abc")), 3);
+        assert_eq!(edit_distance(&chars("kitten"), &chars("sitting")), 3);
+        assert_eq!(edit_distance(&chars(""), &chars("abc")), 3);
+    }
+
+    #[test]
+    fn typos_depend_on_term_length() {
+        assert_eq!(find("passowrd", "My Password", true).unwrap().kind, MatchKind::Typos(1));
+        assert_eq!(find("paswrod", "password", true).map(|hit| hit.kind), None);
+        assert_eq!(find("paswrods", "passwords", true).unwrap().kind, MatchKind::Typos(2));
+        // Too short to guess at, and only with typos turned on
+        assert!(find("bnk", "bank", true).is_none());
+        assert!(find("bnak", "bank", false).is_none());
+        // A typo in a word that's still being typed
+        assert_eq!(find("pasw", "password", true).unwrap().kind, MatchKind::Typos(1));
+        // Phrases are matched as written
+        assert!(find("bnak login", "bank login", true).is_none());
+    }
+}
@@ -1,8 +1,9 @@
-use keepass::db::{Group, Node};
+use keepass::db::{Entry, Group, Node};
 use uuid::Uuid;
 
 use super::database::Database;
 use super::error::DatabaseError;
+use super::tree;
 use super::types::GroupData;
 
 impl Database {
@@ -27,207 +28,232 @@ impl Database {
             parent_uuid,
             children,
             icon_id: group.icon_id,
+            enable_searching: Self::parse_group_flag(group.enable_searching.as_deref()),
+            enable_auto_type: Self::parse_group_flag(group.enable_autotype.as_deref()),
+            is_recycle_bin: self.is_recycle_bin(group),
         }
     }
 
-    pub fn create_group(&mut self, name: String, parent_uuid: Option<String>, icon_id: Option<u32>) -> Result<(), DatabaseError> {
-        let parent_group = if let Some(parent_id) = parent_uuid {
-            self.find_group_by_uuid_mut(&parent_id)?
-        } else {
-            &mut self.db.root
-        };
+    /// Sets `EnableSearching`; None inherits the parent's setting
+    pub fn set_group_searching(&mut self, group_uuid: &str, enabled: Option<bool>) -> Result<(), DatabaseError> {
+        self.record_undo("Change group search setting", |db| {
+            let group = db.find_group_by_uuid_mut(group_uuid)?;
+            group.enable_searching = Self::format_group_flag(enabled);
+            Ok(())
+        })
+    }
+
+    /// Sets `EnableAutoType`; None inherits the parent's setting
+    pub fn set_group_auto_type(&mut self, group_uuid: &str, enabled: Option<bool>) -> Result<(), DatabaseError> {
+        self.record_undo("Change group auto-type setting", |db| {
+            let group = db.find_group_by_uuid_mut(group_uuid)?;
+            group.enable_autotype = Self::format_group_flag(enabled);
+            Ok(())
+        })
+    }
+
+    pub(super) fn is_recycle_bin(&self, group: &Group) -> bool {
+        // KeePass treats a missing RecycleBinEnabled as enabled
+        self.db.meta.recyclebin_enabled.unwrap_or(true) && self.db.meta.recyclebin_uuid == Some(group.uuid)
+    }
 
-        let mut new_group = Group {
-            name,
-            ..Default::default()
+    /// Whether entries directly in `group` are searchable, given whether its parent's are
+    pub(super) fn group_searchable(group: &Group, parent_searchable: bool) -> bool {
+        Self::parse_group_flag(group.enable_searching.as_deref()).unwrap_or(parent_searchable)
+    }
+
+    /// Calls `f` for every entry search and stats should see: entries in groups with
+    /// searching enabled, outside the recycle bin.
+    pub(super) fn visit_searchable_entries<'a>(&'a self, f: &mut impl FnMut(&'a Entry, &'a Group)) {
+        let searchable = Self::group_searchable(&self.db.root, true);
+        self.visit_searchable_in(&self.db.root, searchable, f);
+    }
+
+    fn visit_searchable_in<'a>(&'a self, group: &'a Group, searchable: bool, f: &mut impl FnMut(&'a Entry, &'a Group)) {
+        for node in &group.children {
+            match node {
+                Node::Entry(e) if searchable => f(e, group),
+                // A subgroup can turn searching back on, so disabled groups are still walked
+                Node::Group(g) if !self.is_recycle_bin(g) => {
+                    self.visit_searchable_in(g, Self::group_searchable(g, searchable), f)
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// KDBX stores group flags as "true", "false" or "null", the last meaning inherit
+    fn parse_group_flag(value: Option<&str>) -> Option<bool> {
+        match value?.to_ascii_lowercase().as_str() {
+            "true" => Some(true),
+            "false" => Some(false),
+            _ => None,
+        }
+    }
+
+    fn format_group_flag(value: Option<bool>) -> Option<String> {
+        let value = match value {
+            Some(true) => "true",
+            Some(false) => "false",
+            None => "null",
         };
+        Some(value.to_string())
+    }
+
+    pub fn create_group(&mut self, name: String, parent_uuid: Option<String>, icon_id: Option<u32>) -> Result<String, DatabaseError> {
+        self.record_undo("Create group", |db| {
+            let parent_group = if let Some(parent_id) = parent_uuid {
+                db.find_group_by_uuid_mut(&parent_id)?
+            } else {
+                &mut db.db.root
+            };
+
+            let mut new_group = Group {
+                name,
+                ..Default::default()
+            };
         
-        // Generate a unique UUID for the new group
-        new_group.uuid = Uuid::new_v4();
+            // Generate a unique UUID for the new group
+            new_group.uuid = Uuid::new_v4();
         
-        // Set icon ID if provided
-        if let Some(id) = icon_id {
-            new_group.icon_id = Some(id as usize);
-        }
+            // Set icon ID if provided
+            if let Some(id) = icon_id {
+                new_group.icon_id = Some(id as usize);
+            }
 
-        parent_group.add_child(new_group);
-        Ok(())
+            let new_uuid = new_group.uuid.to_string();
+            parent_group.add_child(new_group);
+            Ok(new_uuid)
+        })
     }
 
     pub fn rename_group(&mut self, group_uuid: &str, new_name: String, icon_id: Option<u32>) -> Result<(), DatabaseError> {
-        let group = self.find_group_by_uuid_mut(group_uuid)?;
-        group.name = new_name;
+        self.record_undo("Rename group", |db| {
+            let group = db.find_group_by_uuid_mut(group_uuid)?;
+            group.name = new_name;
         
-        // Update icon ID if provided
-        if let Some(id) = icon_id {
-            group.icon_id = Some(id as usize);
-        }
+            // Update icon ID if provided
+            if let Some(id) = icon_id {
+                group.icon_id = Some(id as usize);
+            }
         
-        Ok(())
+            Ok(())
+        })
     }
 
     pub fn move_group(&mut self, group_uuid: &str, new_parent_uuid: &str) -> Result<(), DatabaseError> {
-        // Can't move root group
-        if group_uuid == self.db.root.uuid.to_string() {
-            return Err(DatabaseError::GroupNotFound);
-        }
+        self.record_undo("Move group", |db| {
+            // Can't move root group
+            if group_uuid == db.db.root.uuid.to_string() {
+                return Err(DatabaseError::GroupNotFound);
+            }
 
-        // Can't move into itself
-        if group_uuid == new_parent_uuid {
-            return Err(DatabaseError::GroupNotFound);
-        }
+            // Can't move into itself
+            if group_uuid == new_parent_uuid {
+                return Err(DatabaseError::GroupNotFound);
+            }
 
-        // Find and remove the group from its current parent
-        let old_parent_uuid = self.find_group_parent_uuid(group_uuid)?;
-        let group_to_move = {
-            let old_parent = self.find_group_by_uuid_mut(&old_parent_uuid)?;
             let uuid = Uuid::parse_str(group_uuid).map_err(|_| DatabaseError::GroupNotFound)?;
-            
-            let mut group_to_move = None;
-            old_parent.children.retain(|node| {
-                if let Node::Group(g) = node {
-                    if g.uuid == uuid {
-                        group_to_move = Some(g.clone());
-                        false
-                    } else {
-                        true
-                    }
-                } else {
-                    true
-                }
-            });
-            
-            group_to_move.ok_or(DatabaseError::GroupNotFound)?
-        };
+            let target = Uuid::parse_str(new_parent_uuid).map_err(|_| DatabaseError::GroupNotFound)?;
+            let group_path = tree::find_group(&db.db.root, &uuid).ok_or(DatabaseError::GroupNotFound)?;
+            let target_path = tree::find_group(&db.db.root, &target).ok_or(DatabaseError::GroupNotFound)?;
+
+            // Nor into one of its own subgroups, which would detach the whole subtree
+            if tree::is_within(&target_path, &group_path) {
+                return Err(DatabaseError::GroupNotFound);
+            }
 
-        // Add to new parent
-        let new_parent = self.find_group_by_uuid_mut(new_parent_uuid)?;
-        new_parent.add_child(group_to_move);
+            let group_to_move = tree::remove_group(&mut db.db.root, &uuid).ok_or(DatabaseError::GroupNotFound)?;
 
-        Ok(())
+            // Add to new parent
+            let new_parent = db.find_group_by_uuid_mut(new_parent_uuid)?;
+            new_parent.add_child(group_to_move);
+
+            Ok(())
+        })
     }
 
     pub fn reorder_group(&mut self, group_uuid: &str, target_index: usize) -> Result<(), DatabaseError> {
-        // Can't reorder root group
-        if group_uuid == self.db.root.uuid.to_string() {
-            return Err(DatabaseError::GroupNotFound);
-        }
+        self.record_undo("Reorder group", |db| {
+            // Can't reorder root group
+            if group_uuid == db.db.root.uuid.to_string() {
+                return Err(DatabaseError::GroupNotFound);
+            }
 
-        // Find parent and current index
-        let parent_uuid = self.find_group_parent_uuid(group_uuid)?;
-        let parent = self.find_group_by_uuid_mut(&parent_uuid)?;
+            // Find parent and current index
+            let parent_uuid = db.find_group_parent_uuid(group_uuid)?;
+            let parent = db.find_group_by_uuid_mut(&parent_uuid)?;
         
-        let uuid = Uuid::parse_str(group_uuid).map_err(|_| DatabaseError::GroupNotFound)?;
+            let uuid = Uuid::parse_str(group_uuid).map_err(|_| DatabaseError::GroupNotFound)?;
         
-        // Find current index
-        let current_index = parent.children.iter().position(|node| {
-            if let Node::Group(g) = node {
-                g.uuid == uuid
-            } else {
-                false
-            }
-        }).ok_or(DatabaseError::GroupNotFound)?;
+            // Find current index
+            let current_index = parent.children.iter().position(|node| {
+                if let Node::Group(g) = node {
+                    g.uuid == uuid
+                } else {
+                    false
+                }
+            }).ok_or(DatabaseError::GroupNotFound)?;
 
-        // Calculate new index (adjust if moving down)
-        let new_index = if target_index > current_index {
-            target_index.min(parent.children.len() - 1)
-        } else {
-            target_index
-        };
+            // Calculate new index (adjust if moving down)
+            let new_index = if target_index > current_index {
+                target_index.min(parent.children.len() - 1)
+            } else {
+                target_index
+            };
 
-        if current_index != new_index {
-            let item = parent.children.remove(current_index);
-            parent.children.insert(new_index, item);
-        }
+            if current_index != new_index {
+                let item = parent.children.remove(current_index);
+                parent.children.insert(new_index, item);
+            }
 
-        Ok(())
+            Ok(())
+        })
     }
 
     pub fn delete_group(&mut self, group_uuid: &str) -> Result<(), DatabaseError> {
-        if group_uuid == self.db.root.uuid.to_string() {
-            return Err(DatabaseError::GroupNotFound);
-        }
-
-        let parent_uuid = self.find_group_parent_uuid(group_uuid)?;
-        let parent = self.find_group_by_uuid_mut(&parent_uuid)?;
-        
-        let uuid = Uuid::parse_str(group_uuid).map_err(|_| DatabaseError::GroupNotFound)?;
-        parent.children.retain(|node| {
-            if let Node::Group(g) = node {
-                g.uuid != uuid
-            } else {
-                true
+        self.record_undo("Delete group", |db| {
+            if group_uuid == db.db.root.uuid.to_string() {
+                return Err(DatabaseError::GroupNotFound);
             }
-        });
-        
-        Ok(())
+
+            let uuid = Uuid::parse_str(group_uuid).map_err(|_| DatabaseError::GroupNotFound)?;
+            tree::remove_group(&mut db.db.root, &uuid).ok_or(DatabaseError::GroupNotFound)?;
+
+            Ok(())
+        })
     }
 
     pub(super) fn find_group_by_uuid(&self, uuid: &str) -> Result<&Group, DatabaseError> {
         let uuid_parsed = Uuid::parse_str(uuid).map_err(|_| DatabaseError::GroupNotFound)?;
-        self.find_group_recursive(&self.db.root, &uuid_parsed)
+        // The tree walk only runs if the index is stale, i.e. inside a mutation
+        self.index
+            .group(&self.db.root, &uuid_parsed)
+            .or_else(|| tree::group_at(&self.db.root, &tree::find_group(&self.db.root, &uuid_parsed)?))
             .ok_or(DatabaseError::GroupNotFound)
     }
 
     pub(super) fn find_group_by_uuid_mut(&mut self, uuid: &str) -> Result<&mut Group, DatabaseError> {
         let uuid_parsed = Uuid::parse_str(uuid).map_err(|_| DatabaseError::GroupNotFound)?;
-        let root_ptr = &mut self.db.root as *mut Group;
-        unsafe { Self::find_group_recursive_mut_static(&mut *root_ptr, &uuid_parsed) }
-            .ok_or(DatabaseError::GroupNotFound)
-    }
-
-    fn find_group_recursive<'a>(&'a self, group: &'a Group, uuid: &Uuid) -> Option<&'a Group> {
-        if &group.uuid == uuid {
-            return Some(group);
+        if self.index.group(&self.db.root, &uuid_parsed).is_some() {
+            return self
+                .index
+                .group_mut(&mut self.db.root, &uuid_parsed)
+                .ok_or(DatabaseError::GroupNotFound);
         }
-
-        for node in &group.children {
-            if let Node::Group(g) = node {
-                if let Some(found) = self.find_group_recursive(g, uuid) {
-                    return Some(found);
-                }
-            }
-        }
-
-        None
-    }
-
-    fn find_group_recursive_mut_static<'a>(
-        group: &'a mut Group,
-        uuid: &Uuid,
-    ) -> Option<&'a mut Group> {
-        if &group.uuid == uuid {
-            return Some(group);
-        }
-
-        for node in &mut group.children {
-            if let Node::Group(g) = node {
-                if let Some(found) = Self::find_group_recursive_mut_static(g, uuid) {
-                    return Some(found);
-                }
-            }
-        }
-
-        None
+        let path = tree::find_group(&self.db.root, &uuid_parsed).ok_or(DatabaseError::GroupNotFound)?;
+        tree::group_at_mut(&mut self.db.root, &path).ok_or(DatabaseError::GroupNotFound)
     }
 
     pub(super) fn find_group_parent_uuid(&self, group_uuid: &str) -> Result<String, DatabaseError> {
         let uuid_parsed = Uuid::parse_str(group_uuid).map_err(|_| DatabaseError::GroupNotFound)?;
-        self.find_group_parent_uuid_recursive(&self.db.root, &uuid_parsed)
+        self.index
+            .group_parent(&self.db.root, &uuid_parsed)
+            .or_else(|| {
+                let path = tree::find_group(&self.db.root, &uuid_parsed)?;
+                tree::group_at(&self.db.root, path.split_last()?.1)
+            })
+            .map(|group| group.uuid.to_string())
             .ok_or(DatabaseError::GroupNotFound)
     }
-
-    fn find_group_parent_uuid_recursive(&self, group: &Group, uuid: &Uuid) -> Option<String> {
-        for node in &group.children {
-            if let Node::Group(g) = node {
-                if &g.uuid == uuid {
-                    return Some(group.uuid.to_string());
-                }
-                if let Some(found) = self.find_group_parent_uuid_recursive(g, uuid) {
-                    return Some(found);
-                }
-            }
-        }
-        None
-    }
 }
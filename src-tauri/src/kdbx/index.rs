@@ -0,0 +1,208 @@
+use keepass::db::{Entry, Group, Node};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use super::tree::{group_at, group_at_mut, NodePath};
+
+/// UUID -> position index over the group tree, so lookups don't have to walk
+/// the whole tree. Only depends on keepass-rs types and the `tree` module so the
+/// benchmark in `benches/uuid_index.rs` can compile both on their own.
+///
+/// The index is rebuilt after every mutation that moves nodes around rather than
+/// patched, since one removal shifts the positions of all later siblings and
+/// their subtrees. Edits that leave the shape alone keep the index as it is.
+/// Lookups check the node they land on, so a stale position (e.g. halfway
+/// through a mutation) is reported as a miss instead of returning the wrong node.
+#[derive(Default)]
+pub struct TreeIndex {
+    // Entry position is the path of its parent group plus its index in that group
+    entries: HashMap<Uuid, (NodePath, usize)>,
+    groups: HashMap<Uuid, NodePath>,
+}
+
+impl TreeIndex {
+    pub fn build(root: &Group) -> Self {
+        let mut index = Self::default();
+        index.add_group(root, Vec::new());
+        index
+    }
+
+    fn add_group(&mut self, group: &Group, path: NodePath) {
+        for (i, node) in group.children.iter().enumerate() {
+            match node {
+                Node::Entry(e) => {
+                    self.entries.insert(e.uuid, (path.clone(), i));
+                }
+                Node::Group(g) => {
+                    let mut child_path = path.clone();
+                    child_path.push(i);
+                    self.add_group(g, child_path);
+                }
+            }
+        }
+        self.groups.insert(group.uuid, path);
+    }
+
+    pub fn entry<'a>(&self, root: &'a Group, uuid: &Uuid) -> Option<&'a Entry> {
+        let (group_path, child) = self.entries.get(uuid)?;
+        match group_at(root, group_path)?.children.get(*child)? {
+            Node::Entry(e) if e.uuid == *uuid => Some(e),
+            _ => None,
+        }
+    }
+
+    pub fn entry_mut<'a>(&self, root: &'a mut Group, uuid: &Uuid) -> Option<&'a mut Entry> {
+        let (group_path, child) = self.entries.get(uuid)?;
+        match group_at_mut(root, group_path)?.children.get_mut(*child)? {
+            Node::Entry(e) if e.uuid == *uuid => Some(e),
+            _ => None,
+        }
+    }
+
+    /// The group that directly contains the entry
+    pub fn entry_parent<'a>(&self, root: &'a Group, uuid: &Uuid) -> Option<&'a Group> {
+        self.entry(root, uuid)?;
+        let (group_path, _) = self.entries.get(uuid)?;
+        group_at(root, group_path)
+    }
+
+    pub fn group<'a>(&self, root: &'a Group, uuid: &Uuid) -> Option<&'a Group> {
+        group_at(root, self.groups.get(uuid)?).filter(|g| g.uuid == *uuid)
+    }
+
+    pub fn group_mut<'a>(&self, root: &'a mut Group, uuid: &Uuid) -> Option<&'a mut Group> {
+        group_at_mut(root, self.groups.get(uuid)?).filter(|g| g.uuid == *uuid)
+    }
+
+    /// Whether both trees hold the same nodes at the same positions, i.e. whether an
+    /// index built for one also fits the other. Much cheaper than a rebuild.
+    pub fn same_shape(a: &Group, b: &Group) -> bool {
+        a.uuid == b.uuid
+            && a.children.len() == b.children.len()
+            && a.children.iter().zip(&b.children).all(|pair| match pair {
+                (Node::Entry(x), Node::Entry(y)) => x.uuid == y.uuid,
+                (Node::Group(x), Node::Group(y)) => Self::same_shape(x, y),
+                _ => false,
+            })
+    }
+
+    /// The group that directly contains the group; None for the root
+    pub fn group_parent<'a>(&self, root: &'a Group, uuid: &Uuid) -> Option<&'a Group> {
+        self.group(root, uuid)?;
+        let (_, parent_path) = self.groups.get(uuid)?.split_last()?;
+        group_at(root, parent_path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    // Not `crate::kdbx::tree`: the benchmark includes this file next to its own copy
+    use super::super::tree;
+
+    // Root
+    // ├── a (entry)
+    // ├── Work
+    // │   ├── b (entry)
+    // │   └── Servers
+    // │       └── c (entry)
+    // └── Personal
+    fn fixture() -> (Group, [Uuid; 3], [Uuid; 3]) {
+        let (a, b, c) = (Entry::new(), Entry::new(), Entry::new());
+        let entries = [a.uuid, b.uuid, c.uuid];
+
+        let mut servers = Group::new("Servers");
+        servers.add_child(c);
+        let mut work = Group::new("Work");
+        work.add_child(b);
+        let personal = Group::new("Personal");
+        let groups = [work.uuid, servers.uuid, personal.uuid];
+        work.add_child(servers);
+
+        let mut root = Group::new("Root");
+        root.add_child(a);
+        root.add_child(work);
+        root.add_child(personal);
+        (root, entries, groups)
+    }
+
+    #[test]
+    fn finds_entries_groups_and_parents() {
+        let (root, [a, b, c], [work, servers, personal]) = fixture();
+        let index = TreeIndex::build(&root);
+
+        assert_eq!(index.entry(&root, &c).unwrap().uuid, c);
+        assert_eq!(index.entry_parent(&root, &a).unwrap().uuid, root.uuid);
+        assert_eq!(index.entry_parent(&root, &c).unwrap().uuid, servers);
+        assert_eq!(index.group(&root, &personal).unwrap().uuid, personal);
+        assert_eq!(index.group_parent(&root, &servers).unwrap().uuid, work);
+        assert_eq!(index.group_parent(&root, &work).unwrap().uuid, root.uuid);
+        assert!(index.group_parent(&root, &root.uuid).is_none());
+
+        let unknown = Uuid::new_v4();
+        assert!(index.entry(&root, &unknown).is_none());
+        assert!(index.group(&root, &unknown).is_none());
+        // Entries and groups are looked up separately
+        assert!(index.group(&root, &b).is_none());
+        assert!(index.entry(&root, &work).is_none());
+    }
+
+    #[test]
+    fn stale_positions_are_misses() {
+        let (mut root, [a, b, c], [work, servers, personal]) = fixture();
+        let index = TreeIndex::build(&root);
+
+        // Removing `a` shifts Work and Personal one place to the left
+        tree::remove_entry(&mut root, &a).unwrap();
+
+        assert!(index.entry(&root, &a).is_none());
+        assert!(index.group(&root, &work).is_none());
+        assert!(index.group(&root, &personal).is_none());
+        assert!(index.entry_mut(&mut root, &b).is_none());
+        assert!(index.group_mut(&mut root, &servers).is_none());
+        assert!(index.entry_parent(&root, &c).is_none());
+        assert!(index.group_parent(&root, &servers).is_none());
+    }
+
+    #[test]
+    fn a_rebuilt_index_follows_moves_and_deletes() {
+        let (mut root, [a, b, c], [work, servers, personal]) = fixture();
+
+        let moved = tree::remove_group(&mut root, &servers).unwrap();
+        let personal_path = tree::find_group(&root, &personal).unwrap();
+        tree::group_at_mut(&mut root, &personal_path).unwrap().add_child(moved);
+        tree::remove_entry(&mut root, &b).unwrap();
+        let mut index = TreeIndex::build(&root);
+
+        assert_eq!(index.group_parent(&root, &servers).unwrap().uuid, personal);
+        assert_eq!(index.entry_parent(&root, &c).unwrap().uuid, servers);
+        assert_eq!(index.entry(&root, &a).unwrap().uuid, a);
+        assert!(index.entry(&root, &b).is_none());
+        assert!(index.group(&root, &work).unwrap().children.is_empty());
+
+        index.entry_mut(&mut root, &c).unwrap().times.usage_count = 7;
+        assert_eq!(index.entry(&root, &c).unwrap().times.usage_count, 7);
+        index = TreeIndex::build(&root);
+        assert_eq!(index.group_mut(&mut root, &personal).unwrap().children.len(), 1);
+    }
+
+    #[test]
+    fn shape_ignores_content_but_not_positions() {
+        let (root, [a, _, c], [_, servers, _]) = fixture();
+
+        let mut edited = root.clone();
+        let index = TreeIndex::build(&edited);
+        index.entry_mut(&mut edited, &c).unwrap().times.usage_count = 3;
+        index.group_mut(&mut edited, &servers).unwrap().name = "Renamed".to_string();
+        assert!(TreeIndex::same_shape(&root, &edited));
+
+        let mut reordered = root.clone();
+        let moved = tree::remove_entry(&mut reordered, &a).unwrap();
+        reordered.add_child(moved);
+        assert!(!TreeIndex::same_shape(&root, &reordered));
+
+        let mut emptied = root.clone();
+        tree::remove_entry(&mut emptied, &c).unwrap();
+        assert!(!TreeIndex::same_shape(&root, &emptied));
+    }
+}
@@ -0,0 +1,165 @@
+use aes::cipher::{BlockEncrypt, KeyInit};
+use argon2::{Config as Argon2Config, Variant as Argon2Variant, Version as Argon2Version};
+use keepass::config::KdfConfig;
+use std::time::{Duration, Instant};
+
+use super::error::DatabaseError;
+use super::types::{KdfAlgorithm, KdfSettings};
+
+// Lower bounds that keep a hand-picked configuration from being trivially brute-forced.
+// Fewer AES rounds than this are also what get_kdf_info reports as weak.
+pub(super) const MIN_AES_ROUNDS: u64 = 60_000;
+const MIN_ARGON2_MEMORY: u64 = 1024 * 1024;
+// Upper bounds that keep a typo from making the vault impossible to unlock on this machine
+const MAX_ARGON2_MEMORY: u64 = 4 * 1024 * 1024 * 1024;
+const MAX_ARGON2_PARALLELISM: u32 = 256;
+
+// A benchmark runs the KDF for about its target; longer unlock delays aren't useful
+const MIN_BENCHMARK_TARGET: Duration = Duration::from_millis(100);
+const MAX_BENCHMARK_TARGET: Duration = Duration::from_secs(10);
+
+// Enough AES rounds per sample for a stable measurement on fast machines
+const AES_SAMPLE_ROUNDS: u64 = 200_000;
+const AES_MIN_SAMPLE_TIME: Duration = Duration::from_millis(100);
+
+impl KdfSettings {
+    /// Validates user-supplied parameters and converts them into a keepass-rs config.
+    /// Memory is in bytes, as keepass-rs expects.
+    pub fn to_config(&self) -> Result<KdfConfig, DatabaseError> {
+        match *self {
+            KdfSettings::Aes { rounds } => {
+                if rounds < MIN_AES_ROUNDS {
+                    return Err(DatabaseError::InvalidKdf(format!(
+                        "AES-KDF needs at least {} rounds",
+                        MIN_AES_ROUNDS
+                    )));
+                }
+                Ok(KdfConfig::Aes { rounds })
+            }
+            KdfSettings::Argon2d { iterations, memory, parallelism } => {
+                Self::validate_argon2(iterations, memory, parallelism)?;
+                Ok(KdfConfig::Argon2 {
+                    iterations,
+                    memory,
+                    parallelism,
+                    version: Argon2Version::Version13,
+                })
+            }
+            KdfSettings::Argon2id { iterations, memory, parallelism } => {
+                Self::validate_argon2(iterations, memory, parallelism)?;
+                Ok(KdfConfig::Argon2id {
+                    iterations,
+                    memory,
+                    parallelism,
+                    version: Argon2Version::Version13,
+                })
+            }
+        }
+    }
+
+    /// Measures how expensive a KDF can be while unlocking in roughly `target`
+    /// on this machine, like KeePass's "1 second delay" button. For Argon2 the
+    /// memory and lanes are fixed and the iteration count is derived.
+    pub fn benchmark(
+        algorithm: KdfAlgorithm,
+        memory: u64,
+        parallelism: u32,
+        target: Duration,
+    ) -> Result<KdfSettings, DatabaseError> {
+        if !(MIN_BENCHMARK_TARGET..=MAX_BENCHMARK_TARGET).contains(&target) {
+            return Err(DatabaseError::InvalidKdf(format!(
+                "The target unlock time must be between {} ms and {} s",
+                MIN_BENCHMARK_TARGET.as_millis(),
+                MAX_BENCHMARK_TARGET.as_secs()
+            )));
+        }
+        match algorithm {
+            KdfAlgorithm::Aes => {
+                let rounds = Self::benchmark_aes_rounds(target).max(MIN_AES_ROUNDS);
+                Ok(KdfSettings::Aes { rounds })
+            }
+            KdfAlgorithm::Argon2d | KdfAlgorithm::Argon2id => {
+                Self::validate_argon2(1, memory, parallelism)?;
+                let variant = if matches!(algorithm, KdfAlgorithm::Argon2d) {
+                    Argon2Variant::Argon2d
+                } else {
+                    Argon2Variant::Argon2id
+                };
+
+                // Argon2 time grows linearly with iterations, so one timed pass is enough
+                let per_iteration = Self::time_argon2(variant, memory, parallelism)?;
+                let iterations = (target.as_secs_f64() / per_iteration.as_secs_f64().max(f64::EPSILON))
+                    .floor()
+                    .max(1.0) as u64;
+
+                Ok(if matches!(algorithm, KdfAlgorithm::Argon2d) {
+                    KdfSettings::Argon2d { iterations, memory, parallelism }
+                } else {
+                    KdfSettings::Argon2id { iterations, memory, parallelism }
+                })
+            }
+        }
+    }
+
+    fn benchmark_aes_rounds(target: Duration) -> u64 {
+        // Same work as the AES-KDF key transform: two blocks encrypted once per round
+        let cipher = aes::Aes256::new(&[0x4b; 32].into());
+        let mut blocks = [aes::Block::default(), aes::Block::default()];
+
+        let mut rounds_done = 0u64;
+        let start = Instant::now();
+        while start.elapsed() < AES_MIN_SAMPLE_TIME {
+            for _ in 0..AES_SAMPLE_ROUNDS {
+                cipher.encrypt_block(&mut blocks[0]);
+                cipher.encrypt_block(&mut blocks[1]);
+            }
+            rounds_done += AES_SAMPLE_ROUNDS;
+        }
+        std::hint::black_box(&blocks);
+
+        let per_round = start.elapsed().as_secs_f64() / rounds_done as f64;
+        (target.as_secs_f64() / per_round) as u64
+    }
+
+    fn time_argon2(variant: Argon2Variant, memory: u64, parallelism: u32) -> Result<Duration, DatabaseError> {
+        let config = Argon2Config {
+            variant,
+            version: Argon2Version::Version13,
+            mem_cost: (memory / 1024) as u32,
+            time_cost: 1,
+            lanes: parallelism,
+            ..Argon2Config::default()
+        };
+
+        let start = Instant::now();
+        argon2::hash_raw(b"benchmark", &[0u8; 32], &config)
+            .map_err(|e| DatabaseError::InvalidKdf(e.to_string()))?;
+        Ok(start.elapsed())
+    }
+
+    fn validate_argon2(iterations: u64, memory: u64, parallelism: u32) -> Result<(), DatabaseError> {
+        if iterations == 0 {
+            return Err(DatabaseError::InvalidKdf("Argon2 needs at least one iteration".to_string()));
+        }
+        if parallelism == 0 {
+            return Err(DatabaseError::InvalidKdf("Argon2 needs at least one lane".to_string()));
+        }
+        if parallelism > MAX_ARGON2_PARALLELISM {
+            return Err(DatabaseError::InvalidKdf(format!(
+                "Argon2 can use at most {} lanes",
+                MAX_ARGON2_PARALLELISM
+            )));
+        }
+        if memory < MIN_ARGON2_MEMORY {
+            return Err(DatabaseError::InvalidKdf("Argon2 needs at least 1 MiB of memory".to_string()));
+        }
+        if memory > MAX_ARGON2_MEMORY {
+            return Err(DatabaseError::InvalidKdf("Argon2 can use at most 4 GiB of memory".to_string()));
+        }
+        // argon2 requires at least 8 KiB per lane
+        if memory / 1024 < 8 * parallelism as u64 {
+            return Err(DatabaseError::InvalidKdf("Not enough memory for the number of lanes".to_string()));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,268 @@
+use chrono::{NaiveDateTime, Utc};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use super::error::DatabaseError;
+use super::types::{LockInfo, LockStatus};
+
+// A lock that hasn't been refreshed for this long is considered abandoned
+const STALE_AFTER_HOURS: i64 = 12;
+
+// Ids of the locks held by `LockFile`s alive in this process. A lock file written by
+// this process but not in here was left behind and may be taken over.
+fn live_locks() -> &'static Mutex<HashSet<String>> {
+    static LIVE_LOCKS: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+    LIVE_LOCKS.get_or_init(|| Mutex::new(HashSet::new()))
+}
+
+/// Advisory lock file next to the vault (`<name>.kdbx.lock`), in the spirit of
+/// KeePass's `.lock`. It does not stop other programs from writing; it lets
+/// cooperating instances warn before two writers clobber each other.
+pub struct LockFile {
+    path: PathBuf,
+    info: LockInfo,
+    // Someone else's stale lock this one took over, so the user can be told
+    replaced: Option<LockInfo>,
+}
+
+impl LockFile {
+    /// How often an open session refreshes its lock, well within `STALE_AFTER_HOURS`
+    pub const REFRESH_INTERVAL: Duration = Duration::from_secs(30 * 60);
+
+    pub fn lock_path(db_path: &Path) -> PathBuf {
+        let mut name = db_path.as_os_str().to_owned();
+        name.push(".lock");
+        PathBuf::from(name)
+    }
+
+    pub fn status(db_path: &Path) -> LockStatus {
+        match Self::read(&Self::lock_path(db_path)) {
+            Some(info) => LockStatus {
+                locked: true,
+                stale: Self::is_stale(&info),
+                owned_by_us: Self::is_ours(&info),
+                info: Some(info),
+            },
+            None => LockStatus {
+                locked: false,
+                stale: false,
+                owned_by_us: false,
+                info: None,
+            },
+        }
+    }
+
+    /// Takes the lock for `db_path`. A live lock from someone else is reported as
+    /// `Locked`, and one still held elsewhere in this process as a `LockError`. A
+    /// stale one is replaced and kept in `replaced`; one left behind by this process
+    /// is replaced silently.
+    pub fn acquire(db_path: &Path) -> Result<Self, DatabaseError> {
+        let path = Self::lock_path(db_path);
+
+        let mut replaced = None;
+        if let Some(existing) = Self::read(&path) {
+            if Self::is_ours(&existing) {
+                if Self::is_live(&existing) {
+                    return Err(DatabaseError::LockError("This file is already open".to_string()));
+                }
+            } else {
+                if !Self::is_stale(&existing) {
+                    return Err(DatabaseError::Locked {
+                        owner: existing.owner,
+                        host: existing.host,
+                        since: existing.timestamp,
+                    });
+                }
+                replaced = Some(existing);
+            }
+            let _ = fs::remove_file(&path);
+        }
+
+        let info = Self::current_info();
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .map_err(|e| DatabaseError::LockError(format!("Failed to create lock file: {}", e)))?;
+        let contents = serde_json::to_string(&info)
+            .map_err(|e| DatabaseError::LockError(e.to_string()))?;
+        file.write_all(contents.as_bytes())
+            .map_err(|e| DatabaseError::LockError(format!("Failed to write lock file: {}", e)))?;
+
+        if let Ok(mut live) = live_locks().lock() {
+            live.insert(info.id.clone());
+        }
+        Ok(Self { path, info, replaced })
+    }
+
+    pub fn replaced(&self) -> Option<&LockInfo> {
+        self.replaced.as_ref()
+    }
+
+    pub fn break_lock(db_path: &Path) -> Result<(), DatabaseError> {
+        let path = Self::lock_path(db_path);
+        match fs::remove_file(&path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(DatabaseError::LockError(format!("Failed to remove lock file: {}", e))),
+        }
+    }
+
+    pub fn refresh(&self) {
+        self.refresher().refresh();
+    }
+
+    pub fn refresher(&self) -> LockRefresher {
+        LockRefresher {
+            path: self.path.clone(),
+            info: self.info.clone(),
+        }
+    }
+
+    fn read(path: &Path) -> Option<LockInfo> {
+        let contents = fs::read_to_string(path).ok()?;
+        // An unreadable lock still counts as a lock held by somebody
+        Some(serde_json::from_str(&contents).unwrap_or_else(|_| LockInfo {
+            id: String::new(),
+            owner: "unknown".to_string(),
+            host: "unknown".to_string(),
+            pid: 0,
+            timestamp: String::new(),
+        }))
+    }
+
+    fn current_info() -> LockInfo {
+        LockInfo {
+            id: uuid::Uuid::new_v4().to_string(),
+            owner: std::env::var("USER")
+                .or_else(|_| std::env::var("USERNAME"))
+                .unwrap_or_else(|_| "unknown".to_string()),
+            host: Self::hostname(),
+            pid: std::process::id(),
+            timestamp: Utc::now().naive_utc().format("%Y-%m-%dT%H:%M:%S").to_string(),
+        }
+    }
+
+    fn hostname() -> String {
+        std::env::var("COMPUTERNAME")
+            .or_else(|_| std::env::var("HOSTNAME"))
+            .ok()
+            .or_else(|| fs::read_to_string("/etc/hostname").ok().map(|h| h.trim().to_string()))
+            .filter(|h| !h.is_empty())
+            .unwrap_or_else(|| "unknown".to_string())
+    }
+
+    fn is_ours(info: &LockInfo) -> bool {
+        info.pid == std::process::id() && info.host == Self::hostname()
+    }
+
+    fn is_live(info: &LockInfo) -> bool {
+        live_locks().lock().map(|live| live.contains(&info.id)).unwrap_or(true)
+    }
+
+    fn is_stale(info: &LockInfo) -> bool {
+        // A lock from a process on this machine that no longer exists was left by a crash
+        if info.host == Self::hostname() && Self::process_gone(info.pid) {
+            return true;
+        }
+
+        match NaiveDateTime::parse_from_str(&info.timestamp, "%Y-%m-%dT%H:%M:%S") {
+            Ok(timestamp) => Utc::now().naive_utc() - timestamp > chrono::Duration::hours(STALE_AFTER_HOURS),
+            Err(_) => false,
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    fn process_gone(pid: u32) -> bool {
+        pid != 0 && !Path::new(&format!("/proc/{}", pid)).exists()
+    }
+
+    // No cheap liveness check elsewhere without extra dependencies; fall back to the age check
+    #[cfg(not(target_os = "linux"))]
+    fn process_gone(_pid: u32) -> bool {
+        false
+    }
+}
+
+/// What it takes to refresh a `LockFile`, detached from it so the file I/O can
+/// happen without holding on to the database that owns the lock.
+#[derive(Clone)]
+pub struct LockRefresher {
+    path: PathBuf,
+    info: LockInfo,
+}
+
+impl LockRefresher {
+    // Bumps the timestamp so a long-running session never looks stale. A lock that
+    // was broken in the meantime belongs to someone else now and is left alone.
+    pub fn refresh(&self) {
+        if LockFile::read(&self.path).is_none_or(|info| info.id != self.info.id) {
+            eprintln!("LockFile::refresh: Lock file was removed or taken over; not refreshing");
+            return;
+        }
+        let info = LockInfo {
+            timestamp: Utc::now().naive_utc().format("%Y-%m-%dT%H:%M:%S").to_string(),
+            ..self.info.clone()
+        };
+        if let Ok(contents) = serde_json::to_string(&info) {
+            if let Err(e) = fs::write(&self.path, contents) {
+                eprintln!("LockFile::refresh: Failed to update lock file: {}", e);
+            }
+        }
+    }
+}
+
+impl Drop for LockFile {
+    fn drop(&mut self) {
+        if let Ok(mut live) = live_locks().lock() {
+            live.remove(&self.info.id);
+        }
+        // Only remove the file if it is still this exact lock; it may have been broken
+        // and re-taken, possibly by another Database in this same process
+        if Self::read(&self.path).is_some_and(|info| info.id == self.info.id) {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vault_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("lockfile-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir.join(name)
+    }
+
+    #[test]
+    fn a_lock_held_in_this_process_is_not_taken_over() {
+        let path = vault_path("a.kdbx");
+        let first = LockFile::acquire(&path).unwrap();
+        assert!(matches!(LockFile::acquire(&path), Err(DatabaseError::LockError(_))));
+
+        // Another spelling of the same file hits the same lock file
+        let dotted = path.parent().unwrap().join(".").join("a.kdbx");
+        assert!(LockFile::acquire(&dotted).is_err());
+
+        drop(first);
+        assert!(!LockFile::lock_path(&path).exists());
+        assert!(LockFile::acquire(&path).is_ok());
+    }
+
+    #[test]
+    fn a_lock_left_behind_by_this_process_is_replaced_silently() {
+        let path = vault_path("b.kdbx");
+        // As if the Database holding it had been leaked without running Drop
+        let left_behind = LockFile::acquire(&path).unwrap();
+        live_locks().lock().unwrap().remove(&left_behind.info.id);
+        std::mem::forget(left_behind);
+
+        let lock = LockFile::acquire(&path).unwrap();
+        assert!(lock.replaced().is_none());
+    }
+}
@@ -0,0 +1,180 @@
+use keepass::db::{Color, Times};
+
+use super::database::Database;
+use super::error::DatabaseError;
+use super::types::{DatabaseMetadata, MasterKeyStatus};
+
+impl Database {
+    pub fn get_metadata(&self) -> DatabaseMetadata {
+        let meta = &self.db.meta;
+        DatabaseMetadata {
+            name: meta.database_name.clone().unwrap_or_default(),
+            description: meta.database_description.clone().unwrap_or_default(),
+            default_username: meta.default_username.clone().unwrap_or_default(),
+            color: meta
+                .color
+                .as_ref()
+                .map(|c| format!("#{:02X}{:02X}{:02X}", c.r, c.g, c.b)),
+            maintenance_history_days: meta.maintenance_history_days,
+        }
+    }
+
+    /// Updates the editable `Meta` fields. Only fields that actually changed get a
+    /// new `*Changed` timestamp; everything else in `Meta` is left as other clients wrote it.
+    pub fn set_metadata(&mut self, metadata: DatabaseMetadata) -> Result<(), DatabaseError> {
+        self.ensure_writable()?;
+
+        let color = match metadata.color.as_deref().map(str::trim) {
+            Some(hex) if !hex.is_empty() => Some(Self::parse_color(hex)?),
+            _ => None,
+        };
+
+        let now = Times::now();
+        let meta = &mut self.db.meta;
+
+        let name = Some(metadata.name).filter(|s| !s.is_empty());
+        let name_changed = meta.database_name != name;
+        if name_changed {
+            meta.database_name = name;
+            meta.database_name_changed = Some(now);
+        }
+
+        let description = Some(metadata.description).filter(|s| !s.is_empty());
+        let description_changed = meta.database_description != description;
+        if description_changed {
+            meta.database_description = description;
+            meta.database_description_changed = Some(now);
+        }
+
+        let default_username = Some(metadata.default_username).filter(|s| !s.is_empty());
+        let default_username_changed = meta.default_username != default_username;
+        if default_username_changed {
+            meta.default_username = default_username;
+            meta.default_username_changed = Some(now);
+        }
+
+        let mut changed = name_changed || description_changed || default_username_changed;
+        if meta.color != color {
+            meta.color = color;
+            changed = true;
+        }
+        if meta.maintenance_history_days != metadata.maintenance_history_days {
+            meta.maintenance_history_days = metadata.maintenance_history_days;
+            changed = true;
+        }
+
+        // Not part of the undo journal (it only covers the group tree), but needs saving
+        if changed {
+            self.mark_dirty();
+        }
+        Ok(())
+    }
+
+    /// Evaluates `MasterKeyChangeRec` / `MasterKeyChangeForce` (in days, -1 or
+    /// missing meaning disabled) against `MasterKeyChanged`.
+    pub fn get_master_key_status(&self) -> MasterKeyStatus {
+        let meta = &self.db.meta;
+        let age_days = meta
+            .master_key_changed
+            .map(|changed| (Times::now() - changed).num_days());
+        let recommend_after_days = meta.master_key_change_rec.map(|d| d as i64).filter(|d| *d >= 0);
+        let force_after_days = meta.master_key_change_force.map(|d| d as i64).filter(|d| *d >= 0);
+
+        let is_due = |limit: Option<i64>| match (limit, age_days) {
+            (Some(limit), Some(age)) => age >= limit,
+            _ => false,
+        };
+
+        MasterKeyStatus {
+            last_changed: meta
+                .master_key_changed
+                .map(|t| t.format("%Y-%m-%dT%H:%M:%S").to_string()),
+            recommend_after_days,
+            force_after_days,
+            change_recommended: is_due(recommend_after_days),
+            change_forced: is_due(force_after_days),
+        }
+    }
+
+    pub fn set_master_key_policy(
+        &mut self,
+        recommend_after_days: Option<i64>,
+        force_after_days: Option<i64>,
+    ) -> Result<(), DatabaseError> {
+        // Not ensure_writable(): relaxing the policy also lifts a forced key change
+        if self.is_read_only() {
+            return Err(DatabaseError::ReadOnly);
+        }
+
+        // KeePass stores -1 for "disabled"
+        let meta = &mut self.db.meta;
+        meta.master_key_change_rec = Some(recommend_after_days.filter(|d| *d >= 0).unwrap_or(-1) as _);
+        meta.master_key_change_force = Some(force_after_days.filter(|d| *d >= 0).unwrap_or(-1) as _);
+
+        // Same check as on open, so the new policy applies right away
+        self.key_change_forced = self.get_master_key_status().change_forced;
+        self.mark_dirty();
+        Ok(())
+    }
+
+    fn parse_color(hex: &str) -> Result<Color, DatabaseError> {
+        let digits = hex.strip_prefix('#').unwrap_or(hex);
+        let invalid = || DatabaseError::InvalidSettings(format!("Invalid color: {}", hex));
+        if digits.len() != 6 || !digits.is_ascii() {
+            return Err(invalid());
+        }
+
+        let channel = |range: std::ops::Range<usize>| u8::from_str_radix(&digits[range], 16).map_err(|_| invalid());
+        Ok(Color {
+            r: channel(0..2)?,
+            g: channel(2..4)?,
+            b: channel(4..6)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use keepass::config::DatabaseConfig;
+    use keepass::Database as KeepassDatabase;
+
+    fn database() -> Database {
+        Database::in_memory(KeepassDatabase::new(DatabaseConfig::default()))
+    }
+
+    #[test]
+    fn saving_unchanged_metadata_leaves_the_database_clean() {
+        let mut db = database();
+        let mut metadata = db.get_metadata();
+        metadata.color = Some("#1a2B3c".to_string());
+        db.set_metadata(metadata).unwrap();
+        assert!(db.is_dirty());
+
+        let pending = db.prepare_save().unwrap();
+        db.finish_save(pending);
+        let metadata = db.get_metadata();
+        assert_eq!(metadata.color.as_deref(), Some("#1A2B3C"));
+        db.set_metadata(metadata).unwrap();
+        assert!(!db.is_dirty());
+    }
+
+    #[test]
+    fn the_policy_takes_effect_without_reopening() {
+        let mut db = database();
+        db.db.meta.master_key_changed = Some(Times::now() - chrono::Duration::days(100));
+
+        db.set_master_key_policy(None, Some(90)).unwrap();
+        assert!(db.key_change_forced);
+        assert!(matches!(db.ensure_writable(), Err(DatabaseError::MasterKeyChangeRequired)));
+
+        // Clearing the policy is allowed while blocked, and unblocks
+        db.set_master_key_policy(None, None).unwrap();
+        assert!(!db.key_change_forced);
+        assert!(db.ensure_writable().is_ok());
+
+        db.set_master_key_policy(Some(30), Some(365)).unwrap();
+        assert!(!db.key_change_forced);
+        assert!(db.get_master_key_status().change_recommended);
+    }
+}
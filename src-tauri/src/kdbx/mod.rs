@@ -1,10 +1,22 @@
+mod bulk;
 mod database;
 mod entry;
 mod error;
+mod fuzzy;
 mod group;
+mod index;
+mod kdf;
+mod lockfile;
+mod meta;
+mod saved_search;
 mod search;
+mod settings;
 mod stats;
+mod tags;
+mod tree;
 mod types;
+mod undo;
 
 pub use database::Database;
-pub use types::{DashboardStats, EntryData, GroupData, KdfInfo};
+pub use lockfile::{LockFile, LockRefresher};
+pub use types::{AccessAction, BulkOperationResult, DashboardStats, DatabaseMetadata, DatabaseSettings, DatabaseStatus, EntryData, GroupData, KdfAlgorithm, KdfInfo, KdfSettings, LockInfo, LockStatus, MasterKeyStatus, PasswordQuality, SavedSearch, SearchOptions, SearchResult, TagCount, UndoState};
@@ -0,0 +1,120 @@
+use keepass::db::{CustomDataItem, Times, Value};
+use uuid::Uuid;
+
+use super::database::Database;
+use super::error::DatabaseError;
+use super::search::SearchQuery;
+use super::types::{EntryData, SavedSearch, SearchOptions};
+
+/// `Meta` custom data key the saved searches are stored under, as a JSON array, so
+/// they travel with the vault. Other clients keep unknown custom data as is.
+const SAVED_SEARCHES_KEY: &str = "SPM_SavedSearches";
+
+impl Database {
+    pub fn get_saved_searches(&self) -> Result<Vec<SavedSearch>, DatabaseError> {
+        let Some(item) = self.db.meta.custom_data.items.get(SAVED_SEARCHES_KEY) else {
+            return Ok(Vec::new());
+        };
+        let json = match &item.value {
+            Some(Value::Unprotected(json)) => json.as_str(),
+            _ => return Ok(Vec::new()),
+        };
+        // Don't paper over this: the next write would replace what's stored
+        serde_json::from_str(json)
+            .map_err(|e| DatabaseError::InvalidSettings(format!("Saved searches can't be read: {}", e)))
+    }
+
+    pub fn create_saved_search(
+        &mut self,
+        name: String,
+        query: String,
+        options: SearchOptions,
+    ) -> Result<SavedSearch, DatabaseError> {
+        self.ensure_writable()?;
+        let search = Self::validate_saved_search(Uuid::new_v4().to_string(), name, query, options)?;
+
+        let mut searches = self.get_saved_searches()?;
+        searches.push(search.clone());
+        self.store_saved_searches(&searches)?;
+        Ok(search)
+    }
+
+    pub fn update_saved_search(
+        &mut self,
+        uuid: &str,
+        name: String,
+        query: String,
+        options: SearchOptions,
+    ) -> Result<SavedSearch, DatabaseError> {
+        self.ensure_writable()?;
+        let mut searches = self.get_saved_searches()?;
+        let existing = searches
+            .iter_mut()
+            .find(|s| s.uuid == uuid)
+            .ok_or(DatabaseError::SavedSearchNotFound)?;
+        *existing = Self::validate_saved_search(uuid.to_string(), name, query, options)?;
+
+        let search = existing.clone();
+        self.store_saved_searches(&searches)?;
+        Ok(search)
+    }
+
+    pub fn delete_saved_search(&mut self, uuid: &str) -> Result<(), DatabaseError> {
+        self.ensure_writable()?;
+        let mut searches = self.get_saved_searches()?;
+        let count = searches.len();
+        searches.retain(|s| s.uuid != uuid);
+        if searches.len() == count {
+            return Err(DatabaseError::SavedSearchNotFound);
+        }
+        self.store_saved_searches(&searches)
+    }
+
+    /// Runs a saved search like a virtual group: its matching entries, best matches first.
+    /// The query is parsed again every time, so relative dates like `expires:<+30d` move along.
+    pub fn run_saved_search(&self, uuid: &str) -> Result<Vec<EntryData>, DatabaseError> {
+        let search = self
+            .get_saved_searches()?
+            .into_iter()
+            .find(|s| s.uuid == uuid)
+            .ok_or(DatabaseError::SavedSearchNotFound)?;
+        let results = self.search_entries(&search.query, &search.options)?;
+        Ok(results.into_iter().map(|result| result.entry).collect())
+    }
+
+    /// Rejects queries that wouldn't run, so a broken saved search can't be stored
+    fn validate_saved_search(
+        uuid: String,
+        name: String,
+        query: String,
+        options: SearchOptions,
+    ) -> Result<SavedSearch, DatabaseError> {
+        let name = name.trim().to_string();
+        if name.is_empty() {
+            return Err(DatabaseError::InvalidSettings("A saved search needs a name".to_string()));
+        }
+        SearchQuery::parse(&query, &options)?;
+        Ok(SavedSearch { uuid, name, query, options })
+    }
+
+    fn store_saved_searches(&mut self, searches: &[SavedSearch]) -> Result<(), DatabaseError> {
+        let items = &mut self.db.meta.custom_data.items;
+        if searches.is_empty() {
+            items.remove(SAVED_SEARCHES_KEY);
+        } else {
+            let json = serde_json::to_string(searches)
+                .map_err(|e| DatabaseError::InvalidSettings(format!("Saved searches can't be stored: {}", e)))?;
+            items.insert(
+                SAVED_SEARCHES_KEY.to_string(),
+                CustomDataItem {
+                    value: Some(Value::Unprotected(json)),
+                    last_modification_time: Some(Times::now()),
+                },
+            );
+        }
+
+        // Like the other Meta settings, this isn't part of the undo journal
+        self.mark_dirty();
+        Ok(())
+    }
+}
@@ -1,43 +1,1108 @@
+use chrono::{Days, Months, NaiveDate, NaiveDateTime};
+use keepass::db::{Entry, Group, Node, Times, Value};
+use regex::{Regex, RegexBuilder};
+use std::collections::HashSet;
+use std::iter::Peekable;
+use std::str::Chars;
+use uuid::Uuid;
+
 use super::database::Database;
-use super::types::EntryData;
+use super::entry::STANDARD_FIELDS;
+use super::error::DatabaseError;
+use super::fuzzy::{self, Hit};
+use super::stats::WEAK_SCORE;
+use super::tags::parse_tags;
+use super::tree;
+use super::types::{SearchHighlight, SearchOptions, SearchResult};
 
-impl Database {
-    pub fn search_entries(&self, query: &str) -> Vec<EntryData> {
-        let query_lower = query.to_lowercase();
-        self.get_all_entries()
-            .into_iter()
-            .filter(|entry| {
-                entry.title.to_lowercase().contains(&query_lower)
-                    || entry.username.to_lowercase().contains(&query_lower)
-                    || entry.url.to_lowercase().contains(&query_lower)
-                    || entry.notes.to_lowercase().contains(&query_lower)
-                    || entry.tags.to_lowercase().contains(&query_lower)
+/// Fields a term without a qualifier is matched against
+const DEFAULT_FIELDS: &[TextField] = &[
+    TextField::Title,
+    TextField::Username,
+    TextField::Url,
+    TextField::Notes,
+    TextField::Tags,
+    TextField::CustomFields,
+    TextField::Attachments,
+];
+
+/// The default fields plus everything protected, for searches that opted in
+const DEFAULT_AND_PROTECTED_FIELDS: &[TextField] = &[
+    TextField::Title,
+    TextField::Username,
+    TextField::Url,
+    TextField::Notes,
+    TextField::Tags,
+    TextField::CustomFields,
+    TextField::Attachments,
+    TextField::Password,
+    TextField::ProtectedFields,
+];
+
+/// Weight of a hit in a field picked with `attr:`
+const ATTRIBUTE_WEIGHT: u32 = 4;
+
+/// Fields KeePassXC and KeePass 2 plugins store TOTP settings in
+const TOTP_FIELDS: &[&str] = &["otp", "TOTP Seed", "TimeOtp-Secret", "TimeOtp-Secret-Base32"];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum TextField {
+    Title,
+    Username,
+    Url,
+    Notes,
+    Tags,
+    Group,
+    /// Names and values of the unprotected custom fields
+    CustomFields,
+    /// File names of the attachments
+    Attachments,
+    Password,
+    /// Every protected field other than the password
+    ProtectedFields,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Flag {
+    Expired,
+    Weak,
+    Favorite,
+    Reused,
+    Totp,
+    Attachment,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum DateField {
+    Created,
+    Modified,
+    Accessed,
+    Expires,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Comparison {
+    Before,
+    AtOrBefore,
+    On,
+    AtOrAfter,
+    After,
+}
+
+/// A term's text, matched as a substring or as a regex depending on the search options
+#[derive(Debug)]
+enum Pattern {
+    Text { needle: String, case_sensitive: bool },
+    Regex(Regex),
+}
+
+#[derive(Debug)]
+enum Term {
+    Text { fields: &'static [TextField], pattern: Pattern },
+    Tag(Pattern),
+    Attribute { name: String, value: Option<Pattern> },
+    Flag(Flag),
+    Date { field: DateField, comparison: Comparison, date: NaiveDate },
+}
+
+#[derive(Debug)]
+struct Clause {
+    term: Term,
+    negated: bool,
+}
+
+/// Score and highlights of whatever matched; filters like `is:` match with a score of 0
+#[derive(Default)]
+struct TermMatch {
+    score: u32,
+    highlights: Vec<SearchHighlight>,
+}
+
+/// A parsed search query, using the same syntax as KeePassXC:
+///
+/// - `bank login`: every term has to match (title, username, URL, notes, tags, custom
+///   fields or attachment names)
+/// - `"online bank"`: a phrase, matched as a whole
+/// - `title:bank`, `user:`, `url:`, `notes:`, `tag:`, `group:`: match one field only
+/// - `attr:Name=value` / `attr:Name`: a custom field's value, or just its presence
+/// - `-term` or `!term`: exclude entries that match
+/// - `a OR b` (or `a | b`): either side; AND binds tighter, so `a b OR c` is `(a b) OR c`
+/// - `is:expired|weak|favorite|reused`, `has:totp|attachment`
+/// - `created:`, `modified:`, `accessed:`, `expires:` with `<`, `<=`, `>`, `>=` or `=`
+///   and a `YYYY-MM-DD` date, e.g. `modified:<2024-01-01`, or one relative to today,
+///   e.g. `expires:<+30d` or `accessed:<-1y` (`d`, `w`, `m` or `y`)
+///
+/// Results are ranked: each term scores by the best field it hit (title above URL above
+/// notes) and how (whole field, prefix, word start, substring, typo). A term of four or
+/// more characters also matches a word with a typo in it, except in exclusions.
+///
+/// With `SearchOptions::include_history`, text terms also match older versions of an
+/// entry, at half the score.
+///
+/// Text matching is case-insensitive unless `SearchOptions::case_sensitive` is set, and with
+/// `SearchOptions::regex` each term is a regular expression instead of a substring. Passwords
+/// and other protected fields (`password:` / `pw:`) are only searched with `include_protected`.
+/// A qualifier that isn't one of the above (like the `https` in `https://example.com`) is
+/// treated as part of the text. A term with nothing to match, like `""` or a `title:` that
+/// is still being typed, is left out rather than matching everything.
+#[derive(Debug)]
+pub(super) struct SearchQuery {
+    alternatives: Vec<Vec<Clause>>,
+}
+
+enum Token {
+    Or,
+    Word { text: String, qualifier_end: Option<usize>, negated: bool },
+}
+
+/// Everything about an entry the query can look at beyond the entry itself
+struct Candidate<'a> {
+    entry: &'a Entry,
+    group_path: &'a str,
+}
+
+/// Database-wide facts, computed once per search
+struct SearchContext {
+    now: NaiveDateTime,
+    reused: HashSet<String>,
+    include_protected: bool,
+    include_history: bool,
+}
+
+impl SearchQuery {
+    pub(super) fn parse(input: &str, options: &SearchOptions) -> Result<Self, DatabaseError> {
+        let mut alternatives = vec![Vec::new()];
+        for token in tokenize(input) {
+            match token {
+                Token::Or => alternatives.push(Vec::new()),
+                Token::Word { text, qualifier_end, negated } => {
+                    let Some(term) = Term::parse(&text, qualifier_end, options)? else {
+                        continue;
+                    };
+                    if let Some(current) = alternatives.last_mut() {
+                        current.push(Clause { term, negated });
+                    }
+                }
+            }
+        }
+        // A dangling "OR" shouldn't turn the query into match-everything
+        alternatives.retain(|clauses| !clauses.is_empty());
+        Ok(Self { alternatives })
+    }
+
+    fn uses_flag(&self, flag: Flag) -> bool {
+        self.alternatives
+            .iter()
+            .flatten()
+            .any(|clause| matches!(clause.term, Term::Flag(f) if f == flag))
+    }
+
+    /// The best scoring alternative that matches, if any
+    fn evaluate(&self, db: &Database, candidate: &Candidate, context: &SearchContext) -> Option<TermMatch> {
+        // An empty query matches everything, like the plain substring search did
+        if self.alternatives.is_empty() {
+            return Some(TermMatch::default());
+        }
+
+        self.alternatives
+            .iter()
+            .filter_map(|clauses| {
+                clauses.iter().try_fold(TermMatch::default(), |mut total, clause| {
+                    // Typos only widen a search; excluding near misses would hide exact matches
+                    let found = clause.term.evaluate(db, candidate, context, !clause.negated);
+                    match (found, clause.negated) {
+                        (Some(found), false) => {
+                            total.score += found.score;
+                            total.highlights.extend(found.highlights);
+                            Some(total)
+                        }
+                        (None, true) => Some(total),
+                        _ => None,
+                    }
+                })
             })
-            .collect()
+            .max_by_key(|found| found.score)
+    }
+}
+
+fn tokenize(input: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.peek().is_none() {
+            break;
+        }
+
+        // A lone "-" is a search for a dash, not a negation of nothing
+        let negated = matches!(chars.peek(), Some('-' | '!')) && {
+            let mut ahead = chars.clone();
+            ahead.next();
+            ahead.peek().is_some_and(|c| !c.is_whitespace())
+        };
+        if negated {
+            chars.next();
+        }
+
+        let (text, qualifier_end, quoted) = read_word(&mut chars);
+        if !negated && !quoted && (text == "OR" || text == "|") {
+            tokens.push(Token::Or);
+        } else {
+            tokens.push(Token::Word { text, qualifier_end, negated });
+        }
+    }
+    tokens
+}
+
+/// Reads up to the next unquoted whitespace, dropping the quotes. The qualifier is
+/// the text before the first colon, as long as that part isn't quoted.
+fn read_word(chars: &mut Peekable<Chars>) -> (String, Option<usize>, bool) {
+    let mut text = String::new();
+    let mut qualifier_end = None;
+    let mut in_quotes = false;
+    let mut quoted = false;
+
+    while let Some(c) = chars.next_if(|c| in_quotes || !c.is_whitespace()) {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                quoted = true;
+            }
+            ':' if !quoted && qualifier_end.is_none() && !text.is_empty() => {
+                qualifier_end = Some(text.len());
+                text.push(c);
+            }
+            _ => text.push(c),
+        }
+    }
+    (text, qualifier_end, quoted)
+}
+
+impl TermMatch {
+    fn add_hit(&mut self, field: Option<&str>, weight: u32, hit: Hit) {
+        self.add(field, false, None, weight, hit);
+    }
+
+    /// A hit in the field's name rather than its value
+    fn add_name_hit(&mut self, field: &str, weight: u32, hit: Hit) {
+        self.add(Some(field), true, None, weight, hit);
+    }
+
+    /// A hit within the tag at `tag_index` of the parsed tags
+    fn add_tag_hit(&mut self, tag_index: usize, hit: Hit) {
+        let field = TextField::Tags;
+        self.add(field.key(), false, Some(tag_index), field.weight(), hit);
+    }
+
+    fn add(&mut self, field: Option<&str>, in_name: bool, tag_index: Option<usize>, weight: u32, hit: Hit) {
+        self.score = self.score.max(weight * hit.kind.points());
+        if let Some(field) = field.filter(|_| !hit.range.is_empty()) {
+            self.highlights.push(SearchHighlight {
+                field: field.to_string(),
+                in_name,
+                start: hit.range.start,
+                end: hit.range.end,
+                tag_index,
+                history_index: None,
+            });
+        }
+    }
+
+    /// Folds in the hits from an older version of the entry, which count for less
+    fn add_history(&mut self, older: TermMatch, history_index: usize) {
+        self.score = self.score.max(older.score / 2);
+        self.highlights.extend(older.highlights.into_iter().map(|highlight| SearchHighlight {
+            history_index: Some(history_index),
+            ..highlight
+        }));
+    }
+}
+
+impl TextField {
+    /// How much a hit in this field counts towards the ranking
+    fn weight(self) -> u32 {
+        match self {
+            TextField::Title => 16,
+            TextField::Username => 10,
+            TextField::Url => 8,
+            TextField::Tags => 6,
+            TextField::Group | TextField::CustomFields => 4,
+            TextField::Notes | TextField::Attachments => 3,
+            TextField::Password | TextField::ProtectedFields => 2,
+        }
+    }
+
+    /// The KDBX field it reads, for highlights
+    fn key(self) -> Option<&'static str> {
+        match self {
+            TextField::Title => Some("Title"),
+            TextField::Username => Some("UserName"),
+            TextField::Url => Some("URL"),
+            TextField::Notes => Some("Notes"),
+            TextField::Tags => Some("Tags"),
+            TextField::Password => Some("Password"),
+            // The group path isn't a field, and the others are reported by name
+            TextField::Group | TextField::CustomFields | TextField::Attachments | TextField::ProtectedFields => None,
+        }
+    }
+}
+
+impl Pattern {
+    fn new(value: &str, options: &SearchOptions) -> Result<Self, DatabaseError> {
+        if !options.regex {
+            return Ok(Pattern::Text {
+                needle: if options.case_sensitive { value.to_string() } else { fuzzy::fold_case(value) },
+                case_sensitive: options.case_sensitive,
+            });
+        }
+
+        RegexBuilder::new(value)
+            .case_insensitive(!options.case_sensitive)
+            .build()
+            .map(Pattern::Regex)
+            .map_err(|e| DatabaseError::InvalidRegex {
+                pattern: value.to_string(),
+                message: e.to_string(),
+            })
+    }
+
+    /// Finds the first match anywhere in the text. Typos are only tolerated by
+    /// case-insensitive text patterns; the other modes are asked for exactness.
+    fn find(&self, text: &str, typos: bool) -> Option<Hit> {
+        let exact = |range: std::ops::Range<usize>| Hit {
+            kind: fuzzy::classify(text, &range),
+            range,
+        };
+        match self {
+            Pattern::Text { needle, case_sensitive: true } => {
+                text.find(needle.as_str()).map(|start| exact(start..start + needle.len()))
+            }
+            Pattern::Text { needle, case_sensitive: false } => fuzzy::find(needle, text, typos),
+            Pattern::Regex(regex) => regex.find(text).map(|m| exact(m.range())),
+        }
+    }
+
+    /// Matches the text as a whole; a regex still decides for itself with `^` / `$`
+    fn is_exact_match(&self, text: &str) -> bool {
+        match self {
+            Pattern::Text { needle, case_sensitive: true } => text == needle,
+            Pattern::Text { needle, case_sensitive: false } => fuzzy::fold_case(text) == *needle,
+            Pattern::Regex(regex) => regex.is_match(text),
+        }
+    }
+}
+
+impl Term {
+    /// `None` for a term without anything to match
+    fn parse(text: &str, qualifier_end: Option<usize>, options: &SearchOptions) -> Result<Option<Self>, DatabaseError> {
+        let default_fields = if options.include_protected {
+            DEFAULT_AND_PROTECTED_FIELDS
+        } else {
+            DEFAULT_FIELDS
+        };
+        let Some(end) = qualifier_end else {
+            if text.is_empty() {
+                return Ok(None);
+            }
+            return Ok(Some(Term::Text {
+                fields: default_fields,
+                pattern: Pattern::new(text, options)?,
+            }));
+        };
+
+        let value = &text[end + 1..];
+        // A field qualifier with nothing after it would match every entry
+        let field = |fields: &'static [TextField]| -> Result<Option<Self>, DatabaseError> {
+            if value.is_empty() {
+                return Ok(None);
+            }
+            Ok(Some(Term::Text {
+                fields,
+                pattern: Pattern::new(value, options)?,
+            }))
+        };
+
+        Ok(match text[..end].to_lowercase().as_str() {
+            "title" | "t" => field(&[TextField::Title])?,
+            "username" | "user" | "u" => field(&[TextField::Username])?,
+            "url" => field(&[TextField::Url])?,
+            "notes" | "n" => field(&[TextField::Notes])?,
+            "group" | "g" => field(&[TextField::Group])?,
+            "password" | "pw" | "p" => {
+                if !options.include_protected {
+                    return Err(DatabaseError::InvalidQuery(
+                        "Searching passwords requires including protected fields".to_string(),
+                    ));
+                }
+                field(&[TextField::Password])?
+            }
+            "tag" | "tags" if value.trim().is_empty() => None,
+            "tag" | "tags" => Some(Term::Tag(Pattern::new(value.trim(), options)?)),
+            "attr" if value.is_empty() => None,
+            "attr" => Some(match value.split_once('=') {
+                Some((name, value)) => Term::Attribute {
+                    name: name.to_string(),
+                    value: Some(Pattern::new(value, options)?),
+                },
+                None => Term::Attribute {
+                    name: value.to_string(),
+                    value: None,
+                },
+            }),
+            "is" => Some(Term::Flag(match value.to_lowercase().as_str() {
+                "expired" => Flag::Expired,
+                "weak" => Flag::Weak,
+                "favorite" | "favourite" => Flag::Favorite,
+                "reused" => Flag::Reused,
+                other => return Err(DatabaseError::InvalidQuery(format!("Unknown condition 'is:{}'", other))),
+            })),
+            "has" => Some(Term::Flag(match value.to_lowercase().as_str() {
+                "totp" | "otp" => Flag::Totp,
+                "attachment" | "attachments" => Flag::Attachment,
+                other => return Err(DatabaseError::InvalidQuery(format!("Unknown condition 'has:{}'", other))),
+            })),
+            "created" => Some(Self::parse_date(DateField::Created, value)?),
+            "modified" => Some(Self::parse_date(DateField::Modified, value)?),
+            "accessed" => Some(Self::parse_date(DateField::Accessed, value)?),
+            "expires" => Some(Self::parse_date(DateField::Expires, value)?),
+            // Not a qualifier, e.g. "https://..." or "10:30"
+            _ => Some(Term::Text {
+                fields: default_fields,
+                pattern: Pattern::new(text, options)?,
+            }),
+        })
+    }
+
+    fn parse_date(field: DateField, value: &str) -> Result<Self, DatabaseError> {
+        let (comparison, date) = if let Some(rest) = value.strip_prefix("<=") {
+            (Comparison::AtOrBefore, rest)
+        } else if let Some(rest) = value.strip_prefix(">=") {
+            (Comparison::AtOrAfter, rest)
+        } else if let Some(rest) = value.strip_prefix('<') {
+            (Comparison::Before, rest)
+        } else if let Some(rest) = value.strip_prefix('>') {
+            (Comparison::After, rest)
+        } else {
+            (Comparison::On, value.strip_prefix('=').unwrap_or(value))
+        };
+
+        let date = match Self::parse_relative_date(date) {
+            Some(date) => date,
+            None => NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| {
+                DatabaseError::InvalidQuery(format!("Invalid date '{}', expected YYYY-MM-DD or e.g. +30d", date))
+            })?,
+        };
+        Ok(Term::Date { field, comparison, date })
+    }
+
+    /// `+30d`, `-2w`, `+6m`, `-1y`: that far from today. Saved searches are parsed again
+    /// on every run, so their relative dates move along with today.
+    fn parse_relative_date(value: &str) -> Option<NaiveDate> {
+        let forward = match value.chars().next()? {
+            '+' => true,
+            '-' => false,
+            _ => return None,
+        };
+        let rest = &value[1..];
+        let unit = rest.chars().next_back()?;
+        let amount: u32 = rest[..rest.len() - unit.len_utf8()].parse().ok()?;
+        let today = Times::now().date();
+        let (days, months) = match unit {
+            'd' => (amount, 0),
+            'w' => (amount.checked_mul(7)?, 0),
+            'm' => (0, amount),
+            'y' => (0, amount.checked_mul(12)?),
+            _ => return None,
+        };
+        if forward {
+            today.checked_add_days(Days::new(days.into()))?.checked_add_months(Months::new(months))
+        } else {
+            today.checked_sub_days(Days::new(days.into()))?.checked_sub_months(Months::new(months))
+        }
+    }
+
+    fn evaluate(&self, db: &Database, candidate: &Candidate, context: &SearchContext, typos: bool) -> Option<TermMatch> {
+        let entry = candidate.entry;
+        match self {
+            Term::Text { fields, pattern } => {
+                let mut found = Self::text_match(fields, pattern, entry, Some(candidate.group_path), typos);
+                if context.include_history {
+                    let history = entry.history.iter().flat_map(|h| h.get_entries());
+                    for (index, older) in history.enumerate() {
+                        if let Some(older_match) = Self::text_match(fields, pattern, older, None, typos) {
+                            found.get_or_insert_with(TermMatch::default).add_history(older_match, index);
+                        }
+                    }
+                }
+                found
+            }
+            Term::Tag(pattern) => {
+                let tags = parse_tags(entry.get("Tags").unwrap_or(""));
+                let (index, tag) = tags.iter().enumerate().find(|(_, tag)| pattern.is_exact_match(tag))?;
+                let mut found = TermMatch::default();
+                let hit = Hit {
+                    kind: fuzzy::MatchKind::Exact,
+                    range: 0..tag.len(),
+                };
+                found.add_tag_hit(index, hit);
+                Some(found)
+            }
+            Term::Attribute { name, value } => {
+                let mut found: Option<TermMatch> = None;
+                for (key, field_value) in &entry.fields {
+                    if !key.eq_ignore_ascii_case(name) {
+                        continue;
+                    }
+                    let text = match (value, field_value) {
+                        (None, _) => {
+                            found.get_or_insert_with(TermMatch::default);
+                            continue;
+                        }
+                        (Some(_), Value::Unprotected(text)) => Some(text.as_str()),
+                        (Some(_), Value::Protected(_)) if context.include_protected => entry.get(key),
+                        // Protected values are only matched by content when the search opted in
+                        _ => None,
+                    };
+                    let hit = text.zip(value.as_ref()).and_then(|(text, pattern)| pattern.find(text, typos));
+                    if let Some(hit) = hit {
+                        found.get_or_insert_with(TermMatch::default).add_hit(Some(key.as_str()), ATTRIBUTE_WEIGHT, hit);
+                    }
+                }
+                found
+            }
+            Term::Flag(flag) => flag.matches(db, entry, context).then(TermMatch::default),
+            Term::Date { field, comparison, date } => {
+                let time = match field {
+                    DateField::Created => entry.times.get_creation(),
+                    DateField::Modified => entry.times.get_last_modification(),
+                    DateField::Accessed => entry.times.get_last_access(),
+                    DateField::Expires => entry.times.get_expiry().filter(|_| entry.times.expires),
+                };
+                comparison.holds(time?.date(), *date).then(TermMatch::default)
+            }
+        }
+    }
+
+    /// Hits of a text term in one version of an entry. History snapshots have no group path.
+    fn text_match(
+        fields: &[TextField],
+        pattern: &Pattern,
+        entry: &Entry,
+        group_path: Option<&str>,
+        typos: bool,
+    ) -> Option<TermMatch> {
+        let mut found: Option<TermMatch> = None;
+        for &field in fields {
+            let text = match field {
+                TextField::Title => entry.get_title(),
+                TextField::Username => entry.get_username(),
+                TextField::Url => entry.get("URL"),
+                TextField::Notes => entry.get("Notes"),
+                TextField::Tags => {
+                    // Matched tag by tag, so a hit never spans a separator
+                    let tags = parse_tags(entry.get("Tags").unwrap_or(""));
+                    for (index, tag) in tags.iter().enumerate() {
+                        if let Some(hit) = pattern.find(tag, typos) {
+                            found.get_or_insert_with(TermMatch::default).add_tag_hit(index, hit);
+                        }
+                    }
+                    continue;
+                }
+                TextField::Group => group_path,
+                TextField::Password => entry.get_password(),
+                TextField::CustomFields => {
+                    for (key, value) in &entry.fields {
+                        let Value::Unprotected(text) = value else {
+                            continue;
+                        };
+                        if STANDARD_FIELDS.contains(&key.as_str()) {
+                            continue;
+                        }
+                        if let Some(hit) = pattern.find(key, typos) {
+                            found.get_or_insert_with(TermMatch::default).add_name_hit(key, field.weight(), hit);
+                        }
+                        if let Some(hit) = pattern.find(text, typos) {
+                            found.get_or_insert_with(TermMatch::default).add_hit(Some(key.as_str()), field.weight(), hit);
+                        }
+                    }
+                    continue;
+                }
+                TextField::Attachments => {
+                    for (key, value) in &entry.fields {
+                        if !matches!(value, Value::Bytes(_)) {
+                            continue;
+                        }
+                        if let Some(hit) = pattern.find(key, typos) {
+                            found.get_or_insert_with(TermMatch::default).add_name_hit(key, field.weight(), hit);
+                        }
+                    }
+                    continue;
+                }
+                TextField::ProtectedFields => {
+                    for (key, value) in &entry.fields {
+                        if key == "Password" || !matches!(value, Value::Protected(_)) {
+                            continue;
+                        }
+                        if let Some(hit) = entry.get(key).and_then(|text| pattern.find(text, typos)) {
+                            found.get_or_insert_with(TermMatch::default).add_hit(Some(key.as_str()), field.weight(), hit);
+                        }
+                    }
+                    continue;
+                }
+            };
+            if let Some(hit) = text.and_then(|text| pattern.find(text, typos)) {
+                found.get_or_insert_with(TermMatch::default).add_hit(field.key(), field.weight(), hit);
+            }
+        }
+        found
+    }
+}
+
+impl Comparison {
+    /// Compares by calendar day, so `modified:=2024-01-01` matches any time that day
+    fn holds(self, day: NaiveDate, date: NaiveDate) -> bool {
+        match self {
+            Comparison::Before => day < date,
+            Comparison::AtOrBefore => day <= date,
+            Comparison::On => day == date,
+            Comparison::AtOrAfter => day >= date,
+            Comparison::After => day > date,
+        }
     }
+}
 
-    pub fn search_entries_in_group(&self, query: &str, group_uuid: &str) -> Vec<EntryData> {
-        let query_lower = query.to_lowercase();
+impl Flag {
+    fn matches(self, db: &Database, entry: &Entry, context: &SearchContext) -> bool {
+        match self {
+            Flag::Expired => entry.times.expires && entry.times.get_expiry().is_some_and(|t| *t < context.now),
+            Flag::Weak => db.entry_password_quality(entry).score < WEAK_SCORE,
+            Flag::Favorite => entry.get("_Favorite") == Some("true"),
+            Flag::Reused => entry
+                .get_password()
+                .filter(|p| !p.is_empty())
+                .is_some_and(|p| context.reused.contains(&Database::password_hash(p))),
+            Flag::Totp => TOTP_FIELDS
+                .iter()
+                .any(|name| entry.get(name).is_some_and(|v| !v.is_empty())),
+            Flag::Attachment => entry.fields.values().any(|v| matches!(v, Value::Bytes(_))),
+        }
+    }
+}
 
+impl Database {
+    pub fn search_entries(&self, query: &str, options: &SearchOptions) -> Result<Vec<SearchResult>, DatabaseError> {
+        let searchable = Self::group_searchable(&self.db.root, true);
+        self.search_group(&self.db.root.uuid, searchable, query, options)
+    }
+
+    pub fn search_entries_in_group(
+        &self,
+        query: &str,
+        group_uuid: &str,
+        options: &SearchOptions,
+    ) -> Result<Vec<SearchResult>, DatabaseError> {
         // Find the target group; if it doesn't exist, return no results.
-        let Ok(group) = self.find_group_by_uuid(group_uuid) else {
-            return Vec::new();
+        let Ok(uuid) = Uuid::parse_str(group_uuid) else {
+            return Ok(Vec::new());
         };
+        // The user picked this group, so it is searched even if it's excluded itself
+        self.search_group(&uuid, true, query, options)
+    }
 
-        // Collect all entries from the target group and its descendants only.
-        let mut entries_in_group = Vec::new();
-        self.collect_entries(group, &mut entries_in_group);
+    /// Searches the group and its descendants, best matches first. Subgroups with
+    /// searching disabled (KDBX `EnableSearching`, inherited when unset) and the
+    /// recycle bin are skipped.
+    fn search_group(
+        &self,
+        group_uuid: &Uuid,
+        searchable: bool,
+        query: &str,
+        options: &SearchOptions,
+    ) -> Result<Vec<SearchResult>, DatabaseError> {
+        let query = SearchQuery::parse(query, options)?;
 
-        // Filter collected entries by the search query.
-        entries_in_group
-            .into_iter()
-            .filter(|entry| {
-                entry.title.to_lowercase().contains(&query_lower)
-                    || entry.username.to_lowercase().contains(&query_lower)
-                    || entry.url.to_lowercase().contains(&query_lower)
-                    || entry.notes.to_lowercase().contains(&query_lower)
-                    || entry.tags.to_lowercase().contains(&query_lower)
+        let Some(path) = tree::find_group(&self.db.root, group_uuid) else {
+            return Ok(Vec::new());
+        };
+        // `group:` matches the full path, so start from the names of the group's ancestors
+        let group_path = (0..=path.len())
+            .filter_map(|depth| tree::group_at(&self.db.root, &path[..depth]))
+            .map(|g| g.name.as_str())
+            .collect::<Vec<_>>()
+            .join("/");
+        let Some(group) = tree::group_at(&self.db.root, &path) else {
+            return Ok(Vec::new());
+        };
+
+        let context = SearchContext {
+            now: Times::now(),
+            // Reuse is database-wide, so it's only worth the full pass when asked for
+            reused: if query.uses_flag(Flag::Reused) {
+                self.reused_password_hashes()
+            } else {
+                HashSet::new()
+            },
+            include_protected: options.include_protected,
+            include_history: options.include_history,
+        };
+
+        let mut results = Vec::new();
+        self.collect_matches(group, &group_path, searchable, &query, &context, &mut results);
+
+        // Equal scores go to the entries used most, then most recently; the sort is
+        // stable, so anything still tied stays in tree order
+        results.sort_by(|a, b| {
+            b.score
+                .cmp(&a.score)
+                .then_with(|| b.entry.usage_count.cmp(&a.entry.usage_count))
+                .then_with(|| b.entry.last_accessed.cmp(&a.entry.last_accessed))
+        });
+        Ok(results)
+    }
+
+    fn collect_matches(
+        &self,
+        group: &Group,
+        group_path: &str,
+        searchable: bool,
+        query: &SearchQuery,
+        context: &SearchContext,
+        results: &mut Vec<SearchResult>,
+    ) {
+        let group_uuid = group.uuid.to_string();
+        for node in &group.children {
+            match node {
+                Node::Entry(e) if searchable => {
+                    let candidate = Candidate { entry: e, group_path };
+                    if let Some(found) = query.evaluate(self, &candidate, context) {
+                        results.push(SearchResult {
+                            entry: self.convert_entry(e, &group_uuid),
+                            score: found.score,
+                            highlights: found.highlights,
+                        });
+                    }
+                }
+                Node::Group(g) if !self.is_recycle_bin(g) => {
+                    let child_path = format!("{}/{}", group_path, g.name);
+                    let child_searchable = Self::group_searchable(g, searchable);
+                    self.collect_matches(g, &child_path, child_searchable, query, context, results);
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> SearchQuery {
+        SearchQuery::parse(input, &SearchOptions::default()).unwrap()
+    }
+
+    /// Each alternative as its clauses: the text a term looks for, `-` in front when negated
+    fn shape(input: &str) -> Vec<Vec<String>> {
+        parse(input)
+            .alternatives
+            .iter()
+            .map(|clauses| {
+                clauses
+                    .iter()
+                    .map(|clause| {
+                        let text = match &clause.term {
+                            Term::Text { pattern: Pattern::Text { needle, .. }, .. } => needle.clone(),
+                            other => format!("{:?}", other),
+                        };
+                        if clause.negated { format!("-{}", text) } else { text }
+                    })
+                    .collect()
             })
             .collect()
     }
+
+    fn date_term(input: &str) -> (DateField, Comparison, NaiveDate) {
+        match parse(input).alternatives.remove(0).remove(0).term {
+            Term::Date { field, comparison, date } => (field, comparison, date),
+            other => panic!("{} parsed as {:?}", input, other),
+        }
+    }
+
+    fn day(text: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn quoted_phrases_are_one_term() {
+        assert_eq!(shape(r#""Online Bank" login"#), vec![vec!["online bank", "login"]]);
+        // Quotes can start anywhere in a word
+        assert_eq!(shape(r#"title:"my bank""#).len(), 1);
+        match &parse(r#"title:"my bank""#).alternatives[0][0].term {
+            Term::Text { fields, pattern: Pattern::Text { needle, .. } } => {
+                assert_eq!(*fields, &[TextField::Title]);
+                assert_eq!(needle, "my bank");
+            }
+            other => panic!("{:?}", other),
+        }
+        // A quoted qualifier is just text
+        assert_eq!(shape(r#""title:bank""#), vec![vec!["title:bank"]]);
+    }
+
+    #[test]
+    fn negation() {
+        assert_eq!(shape("-old !archived bank"), vec![vec!["-old", "-archived", "bank"]]);
+        assert_eq!(shape(r#"-"old bank""#), vec![vec!["-old bank"]]);
+        // A lone dash is searched for
+        assert_eq!(shape("a - b"), vec![vec!["a", "-", "b"]]);
+    }
+
+    #[test]
+    fn or_binds_looser_than_and() {
+        assert_eq!(shape("a OR b -c"), vec![vec!["a"], vec!["b", "-c"]]);
+        assert_eq!(shape("a b | c"), vec![vec!["a", "b"], vec!["c"]]);
+        // Only the bare uppercase word is an operator
+        assert_eq!(shape(r#"a or "OR""#), vec![vec!["a", "or", "or"]]);
+        assert_eq!(shape("a -OR"), vec![vec!["a", "-or"]]);
+        // Dangling operators don't add a match-everything alternative
+        assert_eq!(shape("OR a OR"), vec![vec!["a"]]);
+    }
+
+    #[test]
+    fn empty_terms_are_left_out() {
+        assert!(shape("title:").is_empty());
+        assert!(shape(r#""" tag: attr: -url:"#).is_empty());
+        assert_eq!(shape("title: bank"), vec![vec!["bank"]]);
+        assert_eq!(shape("user: OR bank"), vec![vec!["bank"]]);
+        // Conditions still need a value
+        assert!(SearchQuery::parse("is:", &SearchOptions::default()).is_err());
+        assert!(SearchQuery::parse("modified:", &SearchOptions::default()).is_err());
+        // Not a qualifier, so the colon is part of the text
+        assert_eq!(shape("note:"), vec![vec!["note:"]]);
+    }
+
+    #[test]
+    fn date_comparisons() {
+        let cases = [
+            ("modified:<2024-01-01", DateField::Modified, Comparison::Before),
+            ("created:<=2024-01-01", DateField::Created, Comparison::AtOrBefore),
+            ("accessed:2024-01-01", DateField::Accessed, Comparison::On),
+            ("accessed:=2024-01-01", DateField::Accessed, Comparison::On),
+            ("expires:>=2024-01-01", DateField::Expires, Comparison::AtOrAfter),
+            ("expires:>2024-01-01", DateField::Expires, Comparison::After),
+        ];
+        for (input, field, comparison) in cases {
+            assert_eq!(date_term(input), (field, comparison, day("2024-01-01")), "{}", input);
+        }
+
+        let date = day("2024-01-01");
+        assert!(Comparison::Before.holds(day("2023-12-31"), date));
+        assert!(!Comparison::Before.holds(date, date));
+        assert!(Comparison::AtOrBefore.holds(date, date));
+        assert!(Comparison::On.holds(date, date));
+        assert!(!Comparison::On.holds(day("2024-01-02"), date));
+        assert!(Comparison::AtOrAfter.holds(date, date));
+        assert!(!Comparison::After.holds(date, date));
+        assert!(Comparison::After.holds(day("2024-01-02"), date));
+    }
+
+    #[test]
+    fn relative_dates() {
+        let today = Times::now().date();
+        assert_eq!(date_term("expires:<+30d").2, today + Days::new(30));
+        assert_eq!(date_term("modified:<-2w").2, today - Days::new(14));
+        assert_eq!(date_term("created:>-1y").2, today.checked_sub_months(Months::new(12)).unwrap());
+        assert_eq!(date_term("expires:<+6m").2, today.checked_add_months(Months::new(6)).unwrap());
+
+        for input in ["modified:<2024-13-01", "expires:<+30", "expires:<30d", "expires:<+xd", "expires:<+1é"] {
+            assert!(SearchQuery::parse(input, &SearchOptions::default()).is_err(), "{}", input);
+        }
+    }
+
+    const STRONG: &str = "vX7#qLp2$wNz9!rTb4";
+
+    fn entry(title: &str, fields: &[(&str, &str)]) -> Entry {
+        let mut entry = Entry::new();
+        entry.fields.insert("Title".to_string(), Value::Unprotected(title.to_string()));
+        entry.fields.insert("Password".to_string(), Value::Protected(STRONG.into()));
+        for (key, value) in fields {
+            entry.fields.insert(key.to_string(), Value::Unprotected(value.to_string()));
+        }
+        entry
+    }
+
+    fn database(root: Group) -> Database {
+        let mut db = keepass::Database::new(keepass::config::DatabaseConfig::default());
+        db.root = root;
+        Database::in_memory(db)
+    }
+
+    fn titles(db: &Database, query: &str, options: &SearchOptions) -> Vec<String> {
+        let mut titles: Vec<String> = db
+            .search_entries(query, options)
+            .unwrap()
+            .into_iter()
+            .map(|result| result.entry.title)
+            .collect();
+        titles.sort();
+        titles
+    }
+
+    #[test]
+    fn expired_and_weak_flags() {
+        let now = Times::now();
+        let mut expired = entry("expired", &[]);
+        expired.times.expires = true;
+        expired.times.set_expiry(now - chrono::Duration::days(1));
+        let mut later = entry("later", &[]);
+        later.times.expires = true;
+        later.times.set_expiry(now + chrono::Duration::days(30));
+        // An expiry date only counts while expiring is switched on
+        let mut switched_off = entry("switched off", &[]);
+        switched_off.times.set_expiry(now - chrono::Duration::days(1));
+        let mut weak = entry("weak", &[]);
+        weak.fields.insert("Password".to_string(), Value::Protected("password1".into()));
+
+        let mut root = Group::new("Root");
+        for e in [expired, later, switched_off, weak] {
+            root.add_child(e);
+        }
+        let db = database(root);
+        let options = SearchOptions::default();
+
+        assert_eq!(titles(&db, "is:expired", &options), vec!["expired"]);
+        assert_eq!(titles(&db, "is:weak", &options), vec!["weak"]);
+        assert_eq!(titles(&db, "-is:weak -is:expired", &options), vec!["later", "switched off"]);
+    }
+
+    #[test]
+    fn the_recycle_bin_is_not_searched() {
+        let mut bin = Group::new("Recycle Bin");
+        bin.add_child(entry("deleted", &[]));
+        let bin_uuid = bin.uuid;
+        let mut root = Group::new("Root");
+        root.add_child(entry("kept", &[]));
+        root.add_child(bin);
+
+        let mut db = database(root);
+        db.db.meta.recyclebin_uuid = Some(bin_uuid);
+        let options = SearchOptions::default();
+        assert_eq!(titles(&db, "e", &options), vec!["kept"]);
+
+        // With the recycle bin turned off it's just another group
+        db.db.meta.recyclebin_enabled = Some(false);
+        assert_eq!(titles(&db, "e", &options), vec!["deleted", "kept"]);
+    }
+
+    #[test]
+    fn enable_searching_is_inherited() {
+        // Root
+        // ├── Hidden (EnableSearching=false)
+        // │   ├── hidden
+        // │   └── Inherits (unset)
+        // │       ├── inherited
+        // │       └── Shown (EnableSearching=true)
+        // │           └── shown
+        // └── top
+        let mut shown = Group::new("Shown");
+        shown.enable_searching = Some("true".to_string());
+        shown.add_child(entry("shown", &[]));
+        let mut inherits = Group::new("Inherits");
+        inherits.add_child(entry("inherited", &[]));
+        inherits.add_child(shown);
+        let mut hidden = Group::new("Hidden");
+        hidden.enable_searching = Some("false".to_string());
+        hidden.add_child(entry("hidden", &[]));
+        hidden.add_child(inherits);
+        let hidden_uuid = hidden.uuid.to_string();
+        let mut root = Group::new("Root");
+        root.add_child(entry("top", &[]));
+        root.add_child(hidden);
+
+        let db = database(root);
+        let options = SearchOptions::default();
+        assert_eq!(titles(&db, "", &options), vec!["shown", "top"]);
+
+        // Searching inside an excluded group the user picked still finds its entries
+        let mut in_group: Vec<String> = db
+            .search_entries_in_group("", &hidden_uuid, &options)
+            .unwrap()
+            .into_iter()
+            .map(|result| result.entry.title)
+            .collect();
+        in_group.sort();
+        assert_eq!(in_group, vec!["hidden", "inherited", "shown"]);
+    }
+
+    #[test]
+    fn custom_fields_and_attachment_names_are_searched() {
+        let mut with_fields = entry("fields", &[("Account ID", "ACME-4711")]);
+        with_fields.fields.insert("invoice.pdf".to_string(), Value::Bytes(b"scanned-blob".to_vec()));
+        let mut root = Group::new("Root");
+        root.add_child(with_fields);
+        root.add_child(entry("other", &[]));
+        let db = database(root);
+        let options = SearchOptions::default();
+
+        let highlights = |query: &str| -> Vec<(String, bool, usize, usize)> {
+            let results = db.search_entries(query, &options).unwrap();
+            assert_eq!(results.len(), 1, "{}", query);
+            results[0].highlights.iter().map(|h| (h.field.clone(), h.in_name, h.start, h.end)).collect()
+        };
+        // A value hit is reported under the field's name, a name hit flagged as such
+        assert_eq!(highlights("4711"), vec![("Account ID".to_string(), false, 5, 9)]);
+        assert_eq!(highlights("account"), vec![("Account ID".to_string(), true, 0, 7)]);
+        assert_eq!(highlights("invoice"), vec![("invoice.pdf".to_string(), true, 0, 7)]);
+        // Attachment contents are not searched
+        assert!(db.search_entries("blob", &options).unwrap().is_empty());
+    }
+
+    #[test]
+    fn protected_fields_only_when_asked() {
+        let mut secret = entry("secret", &[]);
+        secret.fields.insert("PIN".to_string(), Value::Protected("8642".into()));
+        let mut root = Group::new("Root");
+        root.add_child(secret);
+        let db = database(root);
+
+        let mut options = SearchOptions::default();
+        assert!(titles(&db, "8642", &options).is_empty());
+        assert!(titles(&db, STRONG, &options).is_empty());
+
+        options.include_protected = true;
+        assert_eq!(titles(&db, "8642", &options), vec!["secret"]);
+        let results = db.search_entries(STRONG, &options).unwrap();
+        assert_eq!(results[0].highlights[0].field, "Password");
+    }
+
+    #[test]
+    fn history_only_when_asked_and_reported_by_version() {
+        let mut current = entry("renamed", &[]);
+        // History keeps the newest version first
+        let mut history = keepass::db::History::default();
+        for old_title in ["first name", "second name"] {
+            history.add_entry(entry(old_title, &[]));
+        }
+        current.history = Some(history);
+        let mut root = Group::new("Root");
+        root.add_child(current);
+        let db = database(root);
+
+        let mut options = SearchOptions::default();
+        assert!(db.search_entries("second", &options).unwrap().is_empty());
+
+        options.include_history = true;
+        let results = db.search_entries("second", &options).unwrap();
+        assert_eq!(results.len(), 1);
+        let highlight = &results[0].highlights[0];
+        let index = highlight.history_index.unwrap();
+        let version = &results[0].entry.history[index];
+        assert_eq!(version.title, "second name");
+        assert_eq!(&version.title[highlight.start..highlight.end], "second");
+
+        // A hit in the current version counts for more than one in an older version
+        let older = db.search_entries("first", &options).unwrap();
+        assert!(older[0].highlights.iter().all(|h| h.history_index == Some(1)));
+        assert!(db.search_entries("renamed", &options).unwrap()[0].score > older[0].score);
+    }
 }
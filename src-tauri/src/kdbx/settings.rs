@@ -0,0 +1,84 @@
+use keepass::config::{CompressionConfig, DatabaseVersion, InnerCipherConfig, OuterCipherConfig};
+
+use super::database::Database;
+use super::error::DatabaseError;
+use super::types::{CompressionSetting, DatabaseSettings, InnerCipherSetting, OuterCipherSetting};
+
+impl Database {
+    pub fn get_database_settings(&self) -> DatabaseSettings {
+        let config = &self.db.config;
+
+        let outer_cipher = match config.outer_cipher_config {
+            OuterCipherConfig::AES256 => OuterCipherSetting::Aes256,
+            OuterCipherConfig::Twofish => OuterCipherSetting::Twofish,
+            OuterCipherConfig::ChaCha20 => OuterCipherSetting::ChaCha20,
+        };
+        let inner_cipher = match config.inner_cipher_config {
+            InnerCipherConfig::Plain => InnerCipherSetting::Plain,
+            InnerCipherConfig::Salsa20 => InnerCipherSetting::Salsa20,
+            InnerCipherConfig::ChaCha20 => InnerCipherSetting::ChaCha20,
+        };
+        let compression = match config.compression_config {
+            CompressionConfig::None => CompressionSetting::None,
+            CompressionConfig::GZip => CompressionSetting::Gzip,
+        };
+        let kdbx_version = match config.version {
+            DatabaseVersion::KDB(minor) => format!("1.{}", minor),
+            DatabaseVersion::KDB2(minor) => format!("2.{}", minor),
+            DatabaseVersion::KDB3(minor) => format!("3.{}", minor),
+            DatabaseVersion::KDB4(minor) => format!("4.{}", minor),
+        };
+
+        DatabaseSettings {
+            outer_cipher,
+            inner_cipher,
+            compression,
+            kdbx_version,
+        }
+    }
+
+    /// Applies new cipher/compression/format settings. They take effect on the next save.
+    pub fn set_database_settings(&mut self, settings: DatabaseSettings) -> Result<(), DatabaseError> {
+        self.ensure_writable()?;
+
+        // keepass-rs can only write KDBX 4, so older formats are upgraded on save
+        let version = match settings.kdbx_version.as_str() {
+            "4.0" => DatabaseVersion::KDB4(0),
+            "4.1" => DatabaseVersion::KDB4(1),
+            other => {
+                return Err(DatabaseError::InvalidSettings(format!(
+                    "KDBX {} can't be saved; choose 4.0 or 4.1",
+                    other
+                )))
+            }
+        };
+
+        // Plain inner streams would leave protected fields readable in the decrypted XML
+        if matches!(settings.inner_cipher, InnerCipherSetting::Plain) {
+            return Err(DatabaseError::InvalidSettings(
+                "Protected fields require an inner stream cipher".to_string(),
+            ));
+        }
+
+        let config = &mut self.db.config;
+        config.version = version;
+        config.outer_cipher_config = match settings.outer_cipher {
+            OuterCipherSetting::Aes256 => OuterCipherConfig::AES256,
+            OuterCipherSetting::Twofish => OuterCipherConfig::Twofish,
+            OuterCipherSetting::ChaCha20 => OuterCipherConfig::ChaCha20,
+        };
+        config.inner_cipher_config = match settings.inner_cipher {
+            InnerCipherSetting::Plain => InnerCipherConfig::Plain,
+            InnerCipherSetting::Salsa20 => InnerCipherConfig::Salsa20,
+            InnerCipherSetting::ChaCha20 => InnerCipherConfig::ChaCha20,
+        };
+        config.compression_config = match settings.compression {
+            CompressionSetting::None => CompressionConfig::None,
+            CompressionSetting::Gzip => CompressionConfig::GZip,
+        };
+
+        // Not part of the undo journal (it only covers the group tree), but needs saving
+        self.mark_dirty();
+        Ok(())
+    }
+}
@@ -1,43 +1,65 @@
-use keepass::db::{Group, Node};
+use chrono::Datelike;
+use keepass::db::{Entry, Group, Node};
 use sha1::{Sha1, Digest};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::OnceLock;
 
 use super::database::Database;
-use super::types::DashboardStats;
+use super::error::DatabaseError;
+use super::types::{DashboardStats, PasswordQuality};
 
-impl Database {
-    pub(super) fn calculate_password_entropy(&self, password: &str) -> f64 {
-        if password.is_empty() {
-            return 0.0;
-        }
-
-        let mut char_space = 0;
-        let has_lowercase = password.chars().any(|c| c.is_lowercase());
-        let has_uppercase = password.chars().any(|c| c.is_uppercase());
-        let has_digits = password.chars().any(|c| c.is_numeric());
-        let has_symbols = password.chars().any(|c| !c.is_alphanumeric());
-
-        if has_lowercase { char_space += 26; }
-        if has_uppercase { char_space += 26; }
-        if has_digits { char_space += 10; }
-        if has_symbols { char_space += 33; }
+/// Passwords scoring below this count as weak, on the dashboard and for `is:weak`
+pub(super) const WEAK_SCORE: u8 = 3;
 
-        if char_space == 0 {
-            return 0.0;
-        }
+impl Database {
+    pub fn get_password_quality(&self, entry_uuid: &str) -> Result<PasswordQuality, DatabaseError> {
+        let entry = self.find_entry_by_uuid(entry_uuid)?;
+        Ok(self.entry_password_quality(entry))
+    }
 
-        (password.len() as f64) * (char_space as f64).log2()
+    pub(super) fn entry_password_quality(&self, entry: &Entry) -> PasswordQuality {
+        // Someone who knows what the entry is for tries its title and username first
+        estimate_password(
+            entry.get_password().unwrap_or(""),
+            &[entry.get_title().unwrap_or(""), entry.get_username().unwrap_or("")],
+        )
     }
 
     pub(super) fn count_groups(&self, group: &Group) -> usize {
         let mut count = 1;
         for node in &group.children {
             if let Node::Group(g) = node {
-                count += self.count_groups(g);
+                // The recycle bin is left out of the stats along with its entries
+                if !self.is_recycle_bin(g) {
+                    count += self.count_groups(g);
+                }
             }
         }
         count
     }
 
+    // Hash password before using as key to avoid storing plaintext in memory
+    pub(super) fn password_hash(password: &str) -> String {
+        let mut hasher = Sha1::new();
+        hasher.update(password.as_bytes());
+        format!("{:X}", hasher.finalize())
+    }
+
+    /// Hashes of passwords that are used by more than one searchable entry
+    pub(super) fn reused_password_hashes(&self) -> HashSet<String> {
+        let mut counts = HashMap::new();
+        self.visit_searchable_entries(&mut |entry, _| {
+            if let Some(password) = entry.get_password().filter(|p| !p.is_empty()) {
+                *counts.entry(Self::password_hash(password)).or_insert(0) += 1;
+            }
+        });
+        counts
+            .into_iter()
+            .filter(|(_, count)| *count > 1)
+            .map(|(hash, _)| hash)
+            .collect()
+    }
+
     pub fn get_dashboard_stats(&self) -> DashboardStats {
         let all_entries = self.get_all_entries();
         let total_entries = all_entries.len();
@@ -47,26 +69,22 @@ impl Database {
         let mut old_passwords = 0;
         let mut expired_entries = 0;
         let mut favorite_entries = 0;
-        let mut total_entropy = 0.0;
-        let mut password_counts: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+        let mut total_strength = 0.0;
+        let mut password_counts: HashMap<String, usize> = HashMap::new();
 
         let now = chrono::Utc::now().naive_utc();
         let ninety_days_ago = now - chrono::Duration::days(90);
 
         for entry in &all_entries {
-            let entropy = self.calculate_password_entropy(&entry.password);
-            total_entropy += entropy;
+            let quality = estimate_password(&entry.password, &[&entry.title, &entry.username]);
+            total_strength += quality.entropy_bits;
 
-            if entropy < 40.0 {
+            if quality.score < WEAK_SCORE {
                 weak_passwords += 1;
             }
 
             if !entry.password.is_empty() {
-                // Hash password before using as key to avoid storing plaintext in memory
-                let mut hasher = Sha1::new();
-                hasher.update(entry.password.as_bytes());
-                let password_hash = format!("{:X}", hasher.finalize());
-                *password_counts.entry(password_hash).or_insert(0) += 1;
+                *password_counts.entry(Self::password_hash(&entry.password)).or_insert(0) += 1;
             }
 
             if let Some(modified_str) = &entry.modified {
@@ -94,7 +112,7 @@ impl Database {
 
         let reused_passwords = password_counts.values().filter(|&&count| count > 1).count();
         let average_password_strength = if total_entries > 0 {
-            total_entropy / total_entries as f64
+            total_strength / total_entries as f64
         } else {
             0.0
         };
@@ -111,3 +129,846 @@ impl Database {
         }
     }
 }
+
+// Password strength estimation, after Dropbox's zxcvbn: the password is split into the
+// sequence of guessable pieces (dictionary words, keyboard walks, repeats, sequences,
+// dates) an attacker would need the fewest guesses for, with brute force filling the gaps.
+
+/// Longer passwords are only analyzed up to here; the rest can only make them stronger
+const MAX_ANALYZED_CHARS: usize = 100;
+const MIN_WORD_LEN: usize = 3;
+const MAX_WORD_LEN: usize = 32;
+const BRUTEFORCE_CARDINALITY: f64 = 10.0;
+/// Every extra piece in a sequence costs the attacker about this factor more
+const MIN_GUESSES_BEFORE_GROWING_SEQUENCE: f64 = 10_000.0;
+const MIN_SUBMATCH_GUESSES_SINGLE_CHAR: f64 = 10.0;
+const MIN_SUBMATCH_GUESSES_MULTI_CHAR: f64 = 50.0;
+const MIN_YEAR_SPACE: f64 = 20.0;
+const MAX_SEQUENCE_DELTA: i64 = 5;
+/// Keys on a QWERTY keyboard, shifted or not, and how many neighbours each has on average
+const KEYBOARD_STARTING_POSITIONS: f64 = 94.0;
+const KEYBOARD_AVERAGE_DEGREE: f64 = 4.6;
+
+/// Rows of a US QWERTY keyboard: column offset, unshifted keys, shifted keys. The
+/// offsets line each row up so a key's upper neighbours are columns `c` and `c + 1`.
+const QWERTY_ROWS: [(usize, &str, &str); 4] = [
+    (0, "`1234567890-=", "~!@#$%^&*()_+"),
+    (1, "qwertyuiop[]\\", "QWERTYUIOP{}|"),
+    (1, "asdfghjkl;'", "ASDFGHJKL:\""),
+    (1, "zxcvbnm,./", "ZXCVBNM<>?"),
+];
+
+/// Row and column steps to the six neighbours of a key
+const KEY_NEIGHBOURS: [(isize, isize); 6] = [(0, -1), (0, 1), (-1, 0), (-1, 1), (1, -1), (1, 0)];
+
+const L33T_TABLE: [(char, &[char]); 20] = [
+    ('4', &['a']),
+    ('@', &['a']),
+    ('8', &['b']),
+    ('(', &['c']),
+    ('{', &['c']),
+    ('[', &['c']),
+    ('<', &['c']),
+    ('3', &['e']),
+    ('6', &['g']),
+    ('9', &['g']),
+    ('1', &['i', 'l']),
+    ('!', &['i']),
+    ('|', &['i', 'l']),
+    ('0', &['o']),
+    ('$', &['s']),
+    ('5', &['s']),
+    ('7', &['t', 'l']),
+    ('+', &['t']),
+    ('%', &['x']),
+    ('2', &['z']),
+];
+
+const DATE_SEPARATORS: [char; 6] = ['/', '\\', '-', '.', '_', ' '];
+
+const COMMON_PASSWORDS: &str = include_str!("common_passwords.txt");
+const ENGLISH_WORDS: &str = include_str!("english_words.txt");
+
+fn common_password_ranks() -> &'static HashMap<&'static str, usize> {
+    static RANKS: OnceLock<HashMap<&'static str, usize>> = OnceLock::new();
+    RANKS.get_or_init(|| word_ranks(COMMON_PASSWORDS))
+}
+
+fn english_word_ranks() -> &'static HashMap<&'static str, usize> {
+    static RANKS: OnceLock<HashMap<&'static str, usize>> = OnceLock::new();
+    RANKS.get_or_init(|| word_ranks(ENGLISH_WORDS))
+}
+
+/// Rank of every word in a bundled list: its position, starting at 1
+fn word_ranks(list: &'static str) -> HashMap<&'static str, usize> {
+    let mut ranks = HashMap::new();
+    let words = list
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'));
+    for (index, word) in words.enumerate() {
+        ranks.entry(word).or_insert(index + 1);
+    }
+    ranks
+}
+
+/// The dictionary a word was found in
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum WordList {
+    /// The entry's own title and username
+    UserInput,
+    Passwords,
+    English,
+}
+
+#[derive(Clone, Debug)]
+enum Guessable {
+    Dictionary { rank: usize, list: WordList, reversed: bool, l33t: bool },
+    Spatial { turns: usize },
+    Repeat { base_len: usize },
+    Sequence,
+    Year,
+    Date,
+    Bruteforce,
+}
+
+/// A guessable piece of the password; `start..end` are char indices
+#[derive(Clone, Debug)]
+struct PasswordMatch {
+    start: usize,
+    end: usize,
+    kind: Guessable,
+    guesses: f64,
+}
+
+/// The cheapest way found to guess the password up to some position with `len` pieces
+struct Step {
+    last: PasswordMatch,
+    /// Product of the guesses of every piece
+    product: f64,
+    guesses: f64,
+}
+
+/// Estimates how many guesses it would take to find `password`. `user_inputs` (like the
+/// entry's title and username) are treated as the most likely dictionary words.
+pub(super) fn estimate_password(password: &str, user_inputs: &[&str]) -> PasswordQuality {
+    let chars: Vec<char> = password.chars().take(MAX_ANALYZED_CHARS).collect();
+    let user_words = user_dictionary(user_inputs);
+    let (guesses, sequence) = most_guessable(&chars, &user_words);
+    let score = score_for(guesses);
+    let (warning, suggestions) = feedback(&chars, &sequence, score);
+
+    PasswordQuality {
+        guesses,
+        entropy_bits: guesses.log2(),
+        score,
+        warning,
+        suggestions,
+    }
+}
+
+fn score_for(guesses: f64) -> u8 {
+    // Keeps a password of exactly 10^n guesses from landing in the higher score
+    const DELTA: f64 = 5.0;
+    if guesses < 1e3 + DELTA {
+        0
+    } else if guesses < 1e6 + DELTA {
+        1
+    } else if guesses < 1e8 + DELTA {
+        2
+    } else if guesses < 1e10 + DELTA {
+        3
+    } else {
+        4
+    }
+}
+
+fn fold_char(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+fn user_dictionary(user_inputs: &[&str]) -> HashMap<String, usize> {
+    let mut words = HashMap::new();
+    // The whole input as well as its parts, e.g. "jane.doe@example.com" and "jane"
+    let parts = user_inputs
+        .iter()
+        .flat_map(|input| std::iter::once(*input).chain(input.split(|c: char| !c.is_alphanumeric())));
+    let folded = parts
+        .map(|word| word.chars().map(fold_char).collect::<String>())
+        .filter(|word| word.chars().count() >= MIN_WORD_LEN);
+    for (index, word) in folded.enumerate() {
+        words.entry(word).or_insert(index + 1);
+    }
+    words
+}
+
+/// Finds the sequence of pieces that minimizes the guesses needed for `chars`, and that number
+fn most_guessable(chars: &[char], user_words: &HashMap<String, usize>) -> (f64, Vec<PasswordMatch>) {
+    let n = chars.len();
+    if n == 0 {
+        return (1.0, Vec::new());
+    }
+
+    let mut candidates = Vec::new();
+    dictionary_matches(chars, user_words, &mut candidates);
+    spatial_matches(chars, &mut candidates);
+    repeat_matches(chars, user_words, &mut candidates);
+    sequence_matches(chars, &mut candidates);
+    date_matches(chars, &mut candidates);
+    for candidate in &mut candidates {
+        candidate.guesses = candidate.guesses.max(min_submatch_guesses(candidate.end - candidate.start, n));
+    }
+
+    // best[k][len]: the cheapest way to cover chars[..=k] with `len` pieces
+    let mut best: Vec<BTreeMap<usize, Step>> = (0..n).map(|_| BTreeMap::new()).collect();
+    for k in 0..n {
+        for candidate in candidates.iter().filter(|m| m.end == k + 1) {
+            extend_sequences(&mut best, candidate);
+        }
+        for start in 0..=k {
+            extend_sequences(&mut best, &bruteforce_match(chars, start, k + 1));
+        }
+    }
+
+    let Some((mut len, mut step)) = best[n - 1]
+        .iter()
+        .min_by(|(_, a), (_, b)| a.guesses.total_cmp(&b.guesses))
+        .map(|(len, step)| (*len, step))
+    else {
+        return (1.0, Vec::new());
+    };
+    let guesses = step.guesses;
+    let mut sequence = vec![step.last.clone()];
+    while step.last.start > 0 {
+        len -= 1;
+        match best[step.last.start - 1].get(&len) {
+            Some(previous) => step = previous,
+            None => break,
+        }
+        sequence.push(step.last.clone());
+    }
+    sequence.reverse();
+    (guesses, sequence)
+}
+
+fn extend_sequences(best: &mut [BTreeMap<usize, Step>], candidate: &PasswordMatch) {
+    let k = candidate.end - 1;
+    let previous: Vec<(usize, f64)> = if candidate.start == 0 {
+        vec![(0, 1.0)]
+    } else {
+        best[candidate.start - 1]
+            .iter()
+            // Two brute-forced runs in a row are one longer run, which is considered separately
+            .filter(|(_, step)| {
+                !(matches!(candidate.kind, Guessable::Bruteforce) && matches!(step.last.kind, Guessable::Bruteforce))
+            })
+            .map(|(len, step)| (*len, step.product))
+            .collect()
+    };
+
+    for (len, product) in previous {
+        let len = len + 1;
+        let product = product * candidate.guesses;
+        let guesses = factorial(len) * product + MIN_GUESSES_BEFORE_GROWING_SEQUENCE.powi(len as i32 - 1);
+        // Not worth keeping if a sequence with no more pieces is at least as cheap
+        if best[k].range(..=len).any(|(_, step)| step.guesses <= guesses) {
+            continue;
+        }
+        best[k].insert(len, Step { last: candidate.clone(), product, guesses });
+    }
+}
+
+fn min_submatch_guesses(len: usize, password_len: usize) -> f64 {
+    if len >= password_len {
+        1.0
+    } else if len == 1 {
+        MIN_SUBMATCH_GUESSES_SINGLE_CHAR
+    } else {
+        MIN_SUBMATCH_GUESSES_MULTI_CHAR
+    }
+}
+
+fn bruteforce_match(chars: &[char], start: usize, end: usize) -> PasswordMatch {
+    let len = end - start;
+    let mut guesses = BRUTEFORCE_CARDINALITY.powi(len as i32);
+    // A little more than any real match of the same length, so those win ties
+    if len < chars.len() {
+        guesses = guesses.max(min_submatch_guesses(len, chars.len()) + 1.0);
+    }
+    PasswordMatch { start, end, kind: Guessable::Bruteforce, guesses }
+}
+
+fn factorial(n: usize) -> f64 {
+    (1..=n).map(|i| i as f64).product()
+}
+
+fn n_choose_k(n: usize, k: usize) -> f64 {
+    if k > n {
+        return 0.0;
+    }
+    (1..=k).fold(1.0, |acc, i| acc * (n + 1 - i) as f64 / i as f64)
+}
+
+/// Ways to mix `a` of one kind and `b` of another, when an attacker tries the few-changes
+/// variants first
+fn mix_variations(a: usize, b: usize) -> f64 {
+    if a == 0 || b == 0 {
+        2.0
+    } else {
+        (1..=a.min(b)).map(|i| n_choose_k(a + b, i)).sum()
+    }
+}
+
+fn dictionary_matches(chars: &[char], user_words: &HashMap<String, usize>, out: &mut Vec<PasswordMatch>) {
+    let n = chars.len();
+    let lower: Vec<char> = chars.iter().copied().map(fold_char).collect();
+
+    lookup_words(&lower, user_words, |start, end, rank, list| {
+        out.push(PasswordMatch {
+            start,
+            end,
+            kind: Guessable::Dictionary { rank, list, reversed: false, l33t: false },
+            guesses: rank as f64 * uppercase_variations(&chars[start..end]),
+        });
+    });
+
+    let reversed: Vec<char> = lower.iter().rev().copied().collect();
+    lookup_words(&reversed, user_words, |start, end, rank, list| {
+        let (start, end) = (n - end, n - start);
+        out.push(PasswordMatch {
+            start,
+            end,
+            kind: Guessable::Dictionary { rank, list, reversed: true, l33t: false },
+            guesses: rank as f64 * uppercase_variations(&chars[start..end]) * 2.0,
+        });
+    });
+
+    for variant in l33t_variants(&lower) {
+        lookup_words(&variant, user_words, |start, end, rank, list| {
+            // Slices without a substitution were found as they are already
+            if variant[start..end] == lower[start..end] {
+                return;
+            }
+            out.push(PasswordMatch {
+                start,
+                end,
+                kind: Guessable::Dictionary { rank, list, reversed: false, l33t: true },
+                guesses: rank as f64
+                    * uppercase_variations(&chars[start..end])
+                    * l33t_variations(&lower[start..end], &variant[start..end]),
+            });
+        });
+    }
+}
+
+/// Calls `found(start, end, rank, list)` for every slice of `word` in a dictionary
+fn lookup_words(word: &[char], user_words: &HashMap<String, usize>, mut found: impl FnMut(usize, usize, usize, WordList)) {
+    let common = common_password_ranks();
+    let english = english_word_ranks();
+    for start in 0..word.len() {
+        let mut token = String::new();
+        for end in start + 1..=word.len().min(start + MAX_WORD_LEN) {
+            token.push(word[end - 1]);
+            if end - start < MIN_WORD_LEN {
+                continue;
+            }
+            if let Some(&rank) = user_words.get(&token) {
+                found(start, end, rank, WordList::UserInput);
+            }
+            if let Some(&rank) = common.get(token.as_str()) {
+                found(start, end, rank, WordList::Passwords);
+            }
+            if let Some(&rank) = english.get(token.as_str()) {
+                found(start, end, rank, WordList::English);
+            }
+        }
+    }
+}
+
+fn uppercase_variations(token: &[char]) -> f64 {
+    let upper = token.iter().filter(|c| c.is_uppercase()).count();
+    let lower = token.iter().filter(|c| c.is_lowercase()).count();
+    if upper == 0 {
+        return 1.0;
+    }
+    // Capitalized, trailing capital or all caps are what people try first
+    let single_at_edge = upper == 1 && (token[0].is_uppercase() || token[token.len() - 1].is_uppercase());
+    if single_at_edge || lower == 0 {
+        return 2.0;
+    }
+    mix_variations(upper, lower)
+}
+
+/// `lower` with l33t characters read as letters, once per way of reading the ambiguous ones
+fn l33t_variants(lower: &[char]) -> Vec<Vec<char>> {
+    let mut variants = vec![lower.to_vec()];
+    for (sub, letters) in L33T_TABLE.iter().filter(|(sub, _)| lower.contains(sub)) {
+        variants = variants
+            .iter()
+            .flat_map(|variant| {
+                letters
+                    .iter()
+                    .map(move |letter| variant.iter().map(|c| if c == sub { *letter } else { *c }).collect::<Vec<char>>())
+            })
+            .collect();
+    }
+    variants.retain(|variant| variant != lower);
+    variants
+}
+
+fn l33t_variations(subbed: &[char], unsubbed: &[char]) -> f64 {
+    let mut pairs: Vec<(char, char)> = subbed
+        .iter()
+        .zip(unsubbed)
+        .filter(|(sub, letter)| sub != letter)
+        .map(|(sub, letter)| (*sub, *letter))
+        .collect();
+    pairs.sort_unstable();
+    pairs.dedup();
+
+    pairs
+        .into_iter()
+        .map(|(sub, letter)| {
+            let substituted = subbed.iter().filter(|&&c| c == sub).count();
+            let kept = subbed.iter().filter(|&&c| c == letter).count();
+            mix_variations(substituted, kept)
+        })
+        .product()
+}
+
+/// Row, column and whether Shift is needed, for keys on the QWERTY layout
+fn key_position(c: char) -> Option<(usize, usize, bool)> {
+    QWERTY_ROWS.iter().enumerate().find_map(|(row, (offset, unshifted, shifted))| {
+        if let Some(column) = unshifted.chars().position(|k| k == c) {
+            Some((row, offset + column, false))
+        } else {
+            shifted.chars().position(|k| k == c).map(|column| (row, offset + column, true))
+        }
+    })
+}
+
+/// Which of the six neighbours `to` is of `from`, if any
+fn key_direction(from: char, to: char) -> Option<usize> {
+    let (from_row, from_column, _) = key_position(from)?;
+    let (to_row, to_column, _) = key_position(to)?;
+    let step = (
+        to_row as isize - from_row as isize,
+        to_column as isize - from_column as isize,
+    );
+    KEY_NEIGHBOURS.iter().position(|neighbour| *neighbour == step)
+}
+
+fn spatial_matches(chars: &[char], out: &mut Vec<PasswordMatch>) {
+    let is_shifted = |c: char| key_position(c).is_some_and(|(_, _, shifted)| shifted);
+    let mut start = 0;
+    while start + 2 < chars.len() {
+        let mut end = start;
+        let mut turns = 0;
+        let mut direction = None;
+        let mut shifted = usize::from(is_shifted(chars[start]));
+        while end + 1 < chars.len() {
+            let Some(next_direction) = key_direction(chars[end], chars[end + 1]) else {
+                break;
+            };
+            if direction != Some(next_direction) {
+                turns += 1;
+                direction = Some(next_direction);
+            }
+            shifted += usize::from(is_shifted(chars[end + 1]));
+            end += 1;
+        }
+
+        let len = end - start + 1;
+        if len >= 3 {
+            out.push(PasswordMatch {
+                start,
+                end: end + 1,
+                kind: Guessable::Spatial { turns },
+                guesses: spatial_guesses(len, turns, shifted),
+            });
+        }
+        // The key a walk stopped on may start the next one
+        start = end.max(start + 1);
+    }
+}
+
+fn spatial_guesses(len: usize, turns: usize, shifted: usize) -> f64 {
+    let mut guesses = 0.0;
+    for i in 2..=len {
+        for j in 1..=turns.min(i - 1) {
+            guesses += n_choose_k(i - 1, j - 1) * KEYBOARD_STARTING_POSITIONS * KEYBOARD_AVERAGE_DEGREE.powi(j as i32);
+        }
+    }
+    if shifted > 0 {
+        guesses *= mix_variations(shifted, len - shifted);
+    }
+    guesses
+}
+
+fn repeat_matches(chars: &[char], user_words: &HashMap<String, usize>, out: &mut Vec<PasswordMatch>) {
+    let n = chars.len();
+    let mut start = 0;
+    while start < n {
+        // The base repeated over the longest stretch, shortest base first
+        let mut best: Option<(usize, usize)> = None;
+        for base_len in 1..=(n - start) / 2 {
+            let base = &chars[start..start + base_len];
+            let mut count = 1;
+            while start + (count + 1) * base_len <= n
+                && &chars[start + count * base_len..start + (count + 1) * base_len] == base
+            {
+                count += 1;
+            }
+            if count >= 2 && best.is_none_or(|(len, times)| base_len * count > len * times) {
+                best = Some((base_len, count));
+            }
+        }
+
+        let Some((base_len, count)) = best else {
+            start += 1;
+            continue;
+        };
+        let end = start + base_len * count;
+        let (base_guesses, _) = most_guessable(&chars[start..start + base_len], user_words);
+        out.push(PasswordMatch {
+            start,
+            end,
+            kind: Guessable::Repeat { base_len },
+            guesses: base_guesses * count as f64,
+        });
+        start = end;
+    }
+}
+
+fn sequence_matches(chars: &[char], out: &mut Vec<PasswordMatch>) {
+    // Sequences stay within lowercase letters, uppercase letters or digits
+    let class = |c: char| {
+        if c.is_ascii_lowercase() {
+            Some(0)
+        } else if c.is_ascii_uppercase() {
+            Some(1)
+        } else if c.is_ascii_digit() {
+            Some(2)
+        } else {
+            None
+        }
+    };
+
+    let mut start = 0;
+    while start + 2 < chars.len() {
+        let delta = chars[start + 1] as i64 - chars[start] as i64;
+        let mut end = start + 1;
+        if delta != 0 && delta.abs() <= MAX_SEQUENCE_DELTA && class(chars[start]).is_some() {
+            while end < chars.len()
+                && chars[end] as i64 - chars[end - 1] as i64 == delta
+                && class(chars[end]) == class(chars[start])
+            {
+                end += 1;
+            }
+        }
+
+        let len = end - start;
+        if len >= 3 {
+            let first = chars[start];
+            let mut base = if matches!(first, 'a' | 'A' | 'z' | 'Z' | '0' | '1' | '9') {
+                4.0
+            } else if first.is_ascii_digit() {
+                10.0
+            } else {
+                26.0
+            };
+            if delta < 0 {
+                base *= 2.0;
+            }
+            out.push(PasswordMatch { start, end, kind: Guessable::Sequence, guesses: base * len as f64 });
+            start = end - 1;
+        } else {
+            start += 1;
+        }
+    }
+}
+
+fn reference_year() -> i32 {
+    chrono::Utc::now().year()
+}
+
+fn year_space(year: i32) -> f64 {
+    ((year - reference_year()).abs() as f64).max(MIN_YEAR_SPACE)
+}
+
+fn date_matches(chars: &[char], out: &mut Vec<PasswordMatch>) {
+    let n = chars.len();
+    for start in 0..n {
+        if start + 4 <= n {
+            let token: String = chars[start..start + 4].iter().collect();
+            let year = token.parse::<i32>().ok().filter(|_| token.chars().all(|c| c.is_ascii_digit()));
+            if let Some(year) = year.filter(|year| (1900..=2050).contains(year)) {
+                out.push(PasswordMatch { start, end: start + 4, kind: Guessable::Year, guesses: year_space(year) });
+            }
+        }
+        for len in 6..=10 {
+            if start + len > n {
+                break;
+            }
+            if let Some((year, separated)) = parse_date(&chars[start..start + len]) {
+                let separator_guesses = if separated { 4.0 } else { 1.0 };
+                out.push(PasswordMatch {
+                    start,
+                    end: start + len,
+                    kind: Guessable::Date,
+                    guesses: 365.0 * year_space(year) * separator_guesses,
+                });
+            }
+        }
+    }
+}
+
+/// Reads `token` as a day, month and year in any common order, like `311299`,
+/// `1999-12-31` or `12/31/99`. Returns the year and whether separators were used.
+fn parse_date(token: &[char]) -> Option<(i32, bool)> {
+    let text: String = token.iter().collect();
+    let digits = |part: &str| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit());
+
+    if digits(&text) {
+        let splits: &[(usize, usize)] = match text.len() {
+            6 => &[(2, 4)],
+            8 => &[(2, 4), (4, 6)],
+            _ => return None,
+        };
+        return splits
+            .iter()
+            .filter_map(|&(a, b)| date_year([&text[..a], &text[a..b], &text[b..]]))
+            .min_by_key(|year| (year - reference_year()).abs())
+            .map(|year| (year, false));
+    }
+
+    let separator = token.iter().copied().find(|c| !c.is_ascii_digit())?;
+    if !DATE_SEPARATORS.contains(&separator) {
+        return None;
+    }
+    let parts: Vec<&str> = text.split(separator).collect();
+    if parts.len() != 3 || !parts.iter().all(|part| digits(part)) {
+        return None;
+    }
+    date_year([parts[0], parts[1], parts[2]]).map(|year| (year, true))
+}
+
+/// The year of the most plausible reading of day/month/year parts, year first or last
+fn date_year(parts: [&str; 3]) -> Option<i32> {
+    let numbers: Vec<i32> = parts.iter().map(|part| part.parse().ok()).collect::<Option<_>>()?;
+    let is_day_month = |day: i32, month: i32| (1..=31).contains(&day) && (1..=12).contains(&month);
+    let expand_year = |year: i32, digits: usize| match digits {
+        2 if year > 50 => Some(1900 + year),
+        2 => Some(2000 + year),
+        4 => Some(year),
+        _ => None,
+    };
+
+    let mut years = Vec::new();
+    if parts[2].len() <= 2 && parts[1].len() <= 2 && is_day_month(numbers[2], numbers[1]) {
+        years.extend(expand_year(numbers[0], parts[0].len()));
+    }
+    if parts[0].len() <= 2
+        && parts[1].len() <= 2
+        && (is_day_month(numbers[0], numbers[1]) || is_day_month(numbers[1], numbers[0]))
+    {
+        years.extend(expand_year(numbers[2], parts[2].len()));
+    }
+    years
+        .into_iter()
+        .filter(|year| (1000..=2050).contains(year))
+        .min_by_key(|year| (year - reference_year()).abs())
+}
+
+fn feedback(chars: &[char], sequence: &[PasswordMatch], score: u8) -> (Option<String>, Vec<String>) {
+    if sequence.is_empty() {
+        return (
+            None,
+            vec![
+                "Use a few words, avoid common phrases".to_string(),
+                "No need for symbols, digits, or uppercase letters".to_string(),
+            ],
+        );
+    }
+    if score > 2 {
+        return (None, Vec::new());
+    }
+
+    let mut suggestions = vec!["Add another word or two. Uncommon words are better.".to_string()];
+    let Some(longest) = sequence.iter().max_by_key(|m| m.end - m.start) else {
+        return (None, suggestions);
+    };
+
+    let warning = match &longest.kind {
+        Guessable::Dictionary { rank, list, reversed, l33t } => {
+            let token = &chars[longest.start..longest.end];
+            let upper = token.iter().filter(|c| c.is_uppercase()).count();
+            if token[0].is_uppercase() && upper == 1 {
+                suggestions.push("Capitalization doesn't help very much".to_string());
+            } else if upper > 1 && !token.iter().any(|c| c.is_lowercase()) {
+                suggestions.push("All-uppercase is almost as easy to guess as all-lowercase".to_string());
+            }
+            if *reversed && token.len() >= 4 {
+                suggestions.push("Reversed words aren't much harder to guess".to_string());
+            }
+            if *l33t {
+                suggestions.push("Predictable substitutions like '@' instead of 'a' don't help very much".to_string());
+            }
+
+            if *list == WordList::UserInput {
+                Some("Avoid using the entry's title or username in its password")
+            } else if sequence.len() == 1 && !reversed && !l33t {
+                Some(match (list, *rank) {
+                    (WordList::English, _) => "A word by itself is easy to guess",
+                    (_, 1..=10) => "This is a top-10 common password",
+                    (_, 11..=100) => "This is a top-100 common password",
+                    _ => "This is a very common password",
+                })
+            } else if longest.guesses.log10() <= 4.0 {
+                Some("This is similar to a commonly used password")
+            } else {
+                None
+            }
+        }
+        Guessable::Spatial { turns } => {
+            suggestions.push("Use a longer keyboard pattern with more turns".to_string());
+            Some(if *turns == 1 {
+                "Straight rows of keys are easy to guess"
+            } else {
+                "Short keyboard patterns are easy to guess"
+            })
+        }
+        Guessable::Repeat { base_len } => {
+            suggestions.push("Avoid repeated words and characters".to_string());
+            Some(if *base_len == 1 {
+                "Repeats like \"aaa\" are easy to guess"
+            } else {
+                "Repeats like \"abcabcabc\" are only slightly harder to guess than \"abc\""
+            })
+        }
+        Guessable::Sequence => {
+            suggestions.push("Avoid sequences".to_string());
+            Some("Sequences like abc or 6543 are easy to guess")
+        }
+        Guessable::Year => {
+            suggestions.push("Avoid recent years".to_string());
+            suggestions.push("Avoid years that are associated with you".to_string());
+            Some("Recent years are easy to guess")
+        }
+        Guessable::Date => {
+            suggestions.push("Avoid dates and years that are associated with you".to_string());
+            Some("Dates are often easy to guess")
+        }
+        Guessable::Bruteforce => None,
+    };
+    (warning.map(str::to_string), suggestions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn estimate(password: &str) -> PasswordQuality {
+        estimate_password(password, &[])
+    }
+
+    fn warning(password: &str) -> String {
+        estimate(password).warning.unwrap_or_default()
+    }
+
+    #[test]
+    fn top_ten_password_is_weakest() {
+        let quality = estimate("password");
+        assert_eq!(quality.score, 0);
+        assert_eq!(quality.warning.as_deref(), Some("This is a top-10 common password"));
+    }
+
+    #[test]
+    fn l33t_and_reversed_words_are_still_dictionary_words() {
+        let l33t = estimate("p@ssw0rd");
+        assert_eq!(l33t.score, 0);
+        assert_eq!(l33t.warning.as_deref(), Some("This is similar to a commonly used password"));
+        assert!(l33t.suggestions.iter().any(|s| s.contains("Predictable substitutions")));
+
+        let reversed = estimate("drowssap");
+        assert_eq!(reversed.score, 0);
+        assert!(reversed.suggestions.iter().any(|s| s.contains("Reversed words")));
+    }
+
+    #[test]
+    fn keyboard_walks() {
+        assert_eq!(estimate("qwerty").score, 0);
+        let walk = estimate("zxcvbn");
+        assert!(walk.score <= 1);
+        assert_eq!(walk.warning.as_deref(), Some("Straight rows of keys are easy to guess"));
+    }
+
+    #[test]
+    fn repeats_and_sequences() {
+        assert_eq!(estimate("abcabc").score, 0);
+        assert!(warning("abcabc").starts_with("Repeats like \"abcabcabc\""));
+
+        for password in ["abcdef", "6543"] {
+            assert_eq!(estimate(password).score, 0, "{}", password);
+            assert_eq!(warning(password), "Sequences like abc or 6543 are easy to guess");
+        }
+    }
+
+    #[test]
+    fn dates() {
+        for password in ["1999-12-31", "311299"] {
+            assert!(estimate(password).score <= 1, "{}", password);
+            assert_eq!(warning(password), "Dates are often easy to guess");
+        }
+    }
+
+    #[test]
+    fn common_english_words_are_dictionary_words() {
+        let pair = estimate("correcthorse");
+        assert!(pair.score <= 2, "score {} for {} guesses", pair.score, pair.guesses);
+
+        let word = estimate("Ocean");
+        assert!(word.score <= 1);
+        assert_eq!(word.warning.as_deref(), Some("A word by itself is easy to guess"));
+
+        // More words do add up
+        assert!(estimate("correcthorsebatterystaple").score > pair.score);
+    }
+
+    #[test]
+    fn title_and_username_count_as_dictionary_words() {
+        let title = estimate_password("github2024", &["GitHub", "jane@example.com"]);
+        assert!(title.score <= 1);
+        assert_eq!(title.warning.as_deref(), Some("Avoid using the entry's title or username in its password"));
+
+        let username = estimate_password("Zanzibarian", &["Mail", "zanzibarian"]);
+        assert_eq!(username.score, 0);
+        assert_eq!(username.warning.as_deref(), Some("Avoid using the entry's title or username in its password"));
+        // The same word means nothing special for another entry
+        assert!(estimate("Zanzibarian").score > username.score);
+    }
+
+    #[test]
+    fn long_random_password_is_strongest() {
+        let quality = estimate("x7#Kq9!vLm2@Rz8$Wp4^Ty6&");
+        assert_eq!(quality.score, 4);
+        assert_eq!(quality.warning, None);
+        assert!(quality.suggestions.is_empty());
+    }
+
+    #[test]
+    fn empty_password() {
+        let quality = estimate("");
+        assert_eq!(quality.score, 0);
+        assert_eq!(quality.guesses, 1.0);
+        assert_eq!(quality.warning, None);
+        assert!(!quality.suggestions.is_empty());
+    }
+
+    #[test]
+    fn repeated_common_password_stays_weak() {
+        let quality = estimate("Password1!Password1!");
+        assert!(quality.score < WEAK_SCORE);
+        assert!(quality.warning.is_some());
+    }
+}
@@ -0,0 +1,269 @@
+use keepass::db::{Entry, Group, Node, Times, Value};
+use std::collections::{BTreeMap, HashSet};
+
+use super::database::Database;
+use super::error::DatabaseError;
+use super::types::{EntryData, TagCount};
+
+/// KeePass 2 separates tags with `;`, older KeePassXC versions with `,`
+pub(super) const TAG_SEPARATORS: [char; 2] = [';', ','];
+
+/// Splits a KDBX `Tags` value into its tags, trimmed and without duplicates, in stored order
+pub(super) fn parse_tags(raw: &str) -> Vec<String> {
+    normalize_tags(raw.split(TAG_SEPARATORS))
+}
+
+/// Cleans up tags from the frontend, where one item may still hold several separated tags
+pub(super) fn normalize_tags<'a>(tags: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    tags.into_iter()
+        .flat_map(|tag| tag.split(TAG_SEPARATORS))
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty() && seen.insert(*tag))
+        .map(str::to_string)
+        .collect()
+}
+
+/// The stored form; `;` is what current KeePass and KeePassXC write
+pub(super) fn format_tags(tags: &[String]) -> String {
+    tags.join(";")
+}
+
+impl Database {
+    /// Every tag in use outside the recycle bin, with how many entries carry it, by name
+    pub fn get_all_tags(&self) -> Vec<TagCount> {
+        let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+        self.visit_tagged_entries(&self.db.root, &mut |entry, _| {
+            for tag in parse_tags(entry.get("Tags").unwrap_or("")) {
+                *counts.entry(tag).or_default() += 1;
+            }
+        });
+        counts.into_iter().map(|(name, count)| TagCount { name, count }).collect()
+    }
+
+    /// Entries outside the recycle bin carrying all of `tags`, or any of them with `match_all` off
+    pub fn get_entries_with_tags(&self, tags: &[String], match_all: bool) -> Vec<EntryData> {
+        let wanted = normalize_tags(tags.iter().map(String::as_str));
+        if wanted.is_empty() {
+            return Vec::new();
+        }
+
+        let mut entries = Vec::new();
+        self.visit_tagged_entries(&self.db.root, &mut |entry, group| {
+            let entry_tags = parse_tags(entry.get("Tags").unwrap_or(""));
+            let has = |tag: &String| entry_tags.contains(tag);
+            let matches = if match_all { wanted.iter().all(has) } else { wanted.iter().any(has) };
+            if matches {
+                entries.push(self.convert_entry(entry, &group.uuid.to_string()));
+            }
+        });
+        entries
+    }
+
+    /// Renames a tag on every entry, including ones in the recycle bin. Returns the
+    /// UUIDs of the entries that changed.
+    pub fn rename_tag(&mut self, old_name: &str, new_name: &str) -> Result<Vec<String>, DatabaseError> {
+        let (old_name, new_name) = (old_name.trim(), Self::validate_tag_name(new_name)?);
+        if old_name == new_name {
+            return Ok(Vec::new());
+        }
+        // Renaming onto an existing tag would silently merge the two
+        if self.tag_in_use(new_name) {
+            return Err(DatabaseError::InvalidTag(format!("'{}' already exists; merge the tags instead", new_name)));
+        }
+        self.retag("Rename tag", old_name, Some(new_name))
+    }
+
+    /// Replaces `source` with `target` on every entry, dropping the duplicate where an
+    /// entry already had both
+    pub fn merge_tags(&mut self, source: &str, target: &str) -> Result<Vec<String>, DatabaseError> {
+        let (source, target) = (source.trim(), Self::validate_tag_name(target)?);
+        if source == target {
+            return Ok(Vec::new());
+        }
+        self.retag("Merge tags", source, Some(target))
+    }
+
+    pub fn delete_tag(&mut self, name: &str) -> Result<Vec<String>, DatabaseError> {
+        self.retag("Delete tag", name.trim(), None)
+    }
+
+    fn validate_tag_name(name: &str) -> Result<&str, DatabaseError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(DatabaseError::InvalidTag("A tag can't be empty".to_string()));
+        }
+        if name.contains(TAG_SEPARATORS) {
+            return Err(DatabaseError::InvalidTag(format!("'{}' can't contain ';' or ','", name)));
+        }
+        Ok(name)
+    }
+
+    fn tag_in_use(&self, name: &str) -> bool {
+        let mut found = false;
+        Self::visit_all_entries(&self.db.root, &mut |entry| {
+            found = found || parse_tags(entry.get("Tags").unwrap_or("")).iter().any(|t| t == name);
+        });
+        found
+    }
+
+    /// Swaps `tag` for `replacement` (or removes it) on every entry as one undo step
+    fn retag(&mut self, label: &str, tag: &str, replacement: Option<&str>) -> Result<Vec<String>, DatabaseError> {
+        if !self.tag_in_use(tag) {
+            return Err(DatabaseError::InvalidTag(format!("No entry is tagged '{}'", tag)));
+        }
+
+        self.record_undo(label, |db| {
+            let mut changed = Vec::new();
+            let now = Times::now();
+            Self::visit_all_entries_mut(&mut db.db.root, &mut |entry| {
+                let old_tags = parse_tags(entry.get("Tags").unwrap_or(""));
+                if !old_tags.iter().any(|t| t == tag) {
+                    return;
+                }
+                let new_tags = normalize_tags(
+                    old_tags.iter().filter_map(|t| if t == tag { replacement } else { Some(t.as_str()) }),
+                );
+
+                Self::push_history_snapshot(entry);
+                entry.fields.insert("Tags".to_string(), Value::Unprotected(format_tags(&new_tags)));
+                entry.times.set_last_modification(now);
+                changed.push(entry.uuid.to_string());
+            });
+            Ok(changed)
+        })
+    }
+
+    /// Entries tags are listed and filtered from: everything outside the recycle bin
+    fn visit_tagged_entries<'a>(&'a self, group: &'a Group, f: &mut impl FnMut(&'a Entry, &'a Group)) {
+        for node in &group.children {
+            match node {
+                Node::Entry(e) => f(e, group),
+                Node::Group(g) if !self.is_recycle_bin(g) => self.visit_tagged_entries(g, f),
+                _ => {}
+            }
+        }
+    }
+
+    fn visit_all_entries<'a>(group: &'a Group, f: &mut impl FnMut(&'a Entry)) {
+        for node in &group.children {
+            match node {
+                Node::Entry(e) => f(e),
+                Node::Group(g) => Self::visit_all_entries(g, f),
+            }
+        }
+    }
+
+    fn visit_all_entries_mut(group: &mut Group, f: &mut impl FnMut(&mut Entry)) {
+        for node in &mut group.children {
+            match node {
+                Node::Entry(e) => f(e),
+                Node::Group(g) => Self::visit_all_entries_mut(g, f),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kdbx::types::SearchOptions;
+    use keepass::config::DatabaseConfig;
+    use keepass::Database as KeepassDatabase;
+
+    fn tagged(title: &str, tags: &str) -> Entry {
+        let mut entry = Entry::new();
+        entry.fields.insert("Title".to_string(), Value::Unprotected(title.to_string()));
+        entry.fields.insert("Tags".to_string(), Value::Unprotected(tags.to_string()));
+        entry
+    }
+
+    fn database(entries: Vec<Entry>) -> Database {
+        let mut db = KeepassDatabase::new(DatabaseConfig::default());
+        for entry in entries {
+            db.root.add_child(entry);
+        }
+        Database::in_memory(db)
+    }
+
+    fn tags_of(db: &Database, uuid: &str) -> String {
+        db.find_entry_by_uuid(uuid).unwrap().get("Tags").unwrap_or("").to_string()
+    }
+
+    #[test]
+    fn parsing_splits_trims_and_dedups() {
+        assert_eq!(parse_tags(" work ; home,work;;  "), vec!["work", "home"]);
+        assert!(parse_tags("").is_empty());
+        assert_eq!(normalize_tags(["a;b", " c ", "a", ""]), vec!["a", "b", "c"]);
+        assert_eq!(format_tags(&parse_tags("a, b")), "a;b");
+    }
+
+    #[test]
+    fn rename_changes_every_entry_and_refuses_an_existing_name() {
+        let (one, two, other) = (tagged("one", "old;x"), tagged("two", "old"), tagged("other", "x"));
+        let ids = [one.uuid.to_string(), two.uuid.to_string(), other.uuid.to_string()];
+        let mut db = database(vec![one, two, other]);
+
+        assert!(matches!(db.rename_tag("old", "x"), Err(DatabaseError::InvalidTag(_))));
+        assert!(matches!(db.rename_tag("old", "a;b"), Err(DatabaseError::InvalidTag(_))));
+        assert!(matches!(db.rename_tag("missing", "y"), Err(DatabaseError::InvalidTag(_))));
+
+        let mut changed = db.rename_tag(" old ", "new").unwrap();
+        changed.sort();
+        let mut expected = vec![ids[0].clone(), ids[1].clone()];
+        expected.sort();
+        assert_eq!(changed, expected);
+        assert_eq!(tags_of(&db, &ids[0]), "new;x");
+        assert_eq!(tags_of(&db, &ids[1]), "new");
+        assert_eq!(tags_of(&db, &ids[2]), "x");
+
+        // One undo step for the whole rename
+        db.undo().unwrap();
+        assert_eq!(tags_of(&db, &ids[0]), "old;x");
+        assert_eq!(tags_of(&db, &ids[1]), "old");
+    }
+
+    #[test]
+    fn merge_drops_the_duplicate() {
+        let (both, source) = (tagged("both", "a;b"), tagged("source", "a"));
+        let ids = [both.uuid.to_string(), source.uuid.to_string()];
+        let mut db = database(vec![both, source]);
+
+        db.merge_tags("a", "b").unwrap();
+
+        assert_eq!(tags_of(&db, &ids[0]), "b");
+        assert_eq!(tags_of(&db, &ids[1]), "b");
+        let counts: Vec<(String, usize)> = db.get_all_tags().into_iter().map(|t| (t.name, t.count)).collect();
+        assert_eq!(counts, vec![("b".to_string(), 2)]);
+    }
+
+    #[test]
+    fn delete_removes_the_tag_and_keeps_history() {
+        let entry = tagged("entry", "a;b");
+        let id = entry.uuid.to_string();
+        let mut db = database(vec![entry]);
+
+        assert_eq!(db.delete_tag("a").unwrap(), vec![id.clone()]);
+
+        let entry = db.find_entry_by_uuid(&id).unwrap();
+        assert_eq!(entry.get("Tags"), Some("b"));
+        let history = entry.history.as_ref().unwrap().get_entries();
+        assert_eq!(history.last().unwrap().get("Tags"), Some("a;b"));
+        assert!(db.get_entries_with_tags(&["a".to_string()], false).is_empty());
+    }
+
+    #[test]
+    fn tag_highlights_point_into_the_parsed_tags() {
+        let entry = tagged("entry", " work ;, personal");
+        let db = database(vec![entry]);
+
+        let options = SearchOptions::default();
+        for (query, start, end) in [("tag:personal", 0, 8), ("son", 3, 6)] {
+            let results = db.search_entries(query, &options).unwrap();
+            let highlight = results[0].highlights.iter().find(|h| h.field == "Tags").unwrap();
+            assert_eq!(highlight.tag_index, Some(1), "{}", query);
+            assert_eq!((highlight.start, highlight.end), (start, end), "{}", query);
+            assert_eq!(&results[0].entry.tags[1][start..end], &"personal"[start..end]);
+        }
+    }
+}
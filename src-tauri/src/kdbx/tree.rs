@@ -0,0 +1,297 @@
+//! Safe navigation over the keepass-rs group tree.
+//!
+//! Nodes are addressed by paths of child indices from the root. Lookups first
+//! find a path with a shared borrow and then walk it, mutably if needed, so
+//! there is never more than one live borrow into the tree and no `unsafe`.
+//! The tests are Miri-clean: `cargo +nightly miri test kdbx::tree`.
+
+use keepass::db::{Entry, Group, Node};
+use uuid::Uuid;
+
+/// Position of a group as child indices from the root group; empty for the root.
+pub type NodePath = Vec<usize>;
+
+/// Position of an entry: the path of its parent group and its index in that group.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EntryPath {
+    pub group: NodePath,
+    pub index: usize,
+}
+
+pub fn group_at<'a>(root: &'a Group, path: &[usize]) -> Option<&'a Group> {
+    let mut group = root;
+    for &i in path {
+        group = match group.children.get(i)? {
+            Node::Group(g) => g,
+            Node::Entry(_) => return None,
+        };
+    }
+    Some(group)
+}
+
+pub fn group_at_mut<'a>(root: &'a mut Group, path: &[usize]) -> Option<&'a mut Group> {
+    let mut group = root;
+    for &i in path {
+        group = match group.children.get_mut(i)? {
+            Node::Group(g) => g,
+            Node::Entry(_) => return None,
+        };
+    }
+    Some(group)
+}
+
+pub fn entry_at<'a>(root: &'a Group, path: &EntryPath) -> Option<&'a Entry> {
+    match group_at(root, &path.group)?.children.get(path.index)? {
+        Node::Entry(e) => Some(e),
+        Node::Group(_) => None,
+    }
+}
+
+pub fn entry_at_mut<'a>(root: &'a mut Group, path: &EntryPath) -> Option<&'a mut Entry> {
+    match group_at_mut(root, &path.group)?.children.get_mut(path.index)? {
+        Node::Entry(e) => Some(e),
+        Node::Group(_) => None,
+    }
+}
+
+pub fn find_entry(root: &Group, uuid: &Uuid) -> Option<EntryPath> {
+    let mut path = Vec::new();
+    find_entry_in(root, uuid, &mut path)
+}
+
+fn find_entry_in(group: &Group, uuid: &Uuid, path: &mut NodePath) -> Option<EntryPath> {
+    for (i, node) in group.children.iter().enumerate() {
+        match node {
+            Node::Entry(e) if e.uuid == *uuid => {
+                return Some(EntryPath {
+                    group: path.clone(),
+                    index: i,
+                })
+            }
+            Node::Group(g) => {
+                path.push(i);
+                if let Some(found) = find_entry_in(g, uuid, path) {
+                    return Some(found);
+                }
+                path.pop();
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+pub fn find_group(root: &Group, uuid: &Uuid) -> Option<NodePath> {
+    let mut path = Vec::new();
+    find_group_in(root, uuid, &mut path).then_some(path)
+}
+
+fn find_group_in(group: &Group, uuid: &Uuid, path: &mut NodePath) -> bool {
+    if group.uuid == *uuid {
+        return true;
+    }
+    for (i, node) in group.children.iter().enumerate() {
+        if let Node::Group(g) = node {
+            path.push(i);
+            if find_group_in(g, uuid, path) {
+                return true;
+            }
+            path.pop();
+        }
+    }
+    false
+}
+
+/// Detaches the entry from wherever it is in the tree.
+pub fn remove_entry(root: &mut Group, uuid: &Uuid) -> Option<Entry> {
+    let path = find_entry(root, uuid)?;
+    match group_at_mut(root, &path.group)?.children.remove(path.index) {
+        Node::Entry(e) => Some(e),
+        Node::Group(_) => unreachable!("find_entry returned the path of a group"),
+    }
+}
+
+/// Detaches the group and its whole subtree. The root can't be removed.
+pub fn remove_group(root: &mut Group, uuid: &Uuid) -> Option<Group> {
+    let path = find_group(root, uuid)?;
+    let (&index, parent_path) = path.split_last()?;
+    match group_at_mut(root, parent_path)?.children.remove(index) {
+        Node::Group(g) => Some(g),
+        Node::Entry(_) => unreachable!("find_group returned the path of an entry"),
+    }
+}
+
+/// True if `path` is `ancestor` itself or lies somewhere below it.
+pub fn is_within(path: &[usize], ancestor: &[usize]) -> bool {
+    path.starts_with(ancestor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Root
+    // ├── a (entry)
+    // ├── Work
+    // │   ├── b (entry)
+    // │   └── Servers
+    // │       └── c (entry)
+    // └── Personal
+    //     └── d (entry)
+    struct Fixture {
+        root: Group,
+        work: Uuid,
+        servers: Uuid,
+        personal: Uuid,
+        a: Uuid,
+        b: Uuid,
+        c: Uuid,
+        d: Uuid,
+    }
+
+    fn entry(title: &str) -> Entry {
+        let mut entry = Entry::new();
+        entry.fields.insert(
+            "Title".to_string(),
+            keepass::db::Value::Unprotected(title.to_string()),
+        );
+        entry
+    }
+
+    fn fixture() -> Fixture {
+        let (a, b, c, d) = (entry("a"), entry("b"), entry("c"), entry("d"));
+        let (a_id, b_id, c_id, d_id) = (a.uuid, b.uuid, c.uuid, d.uuid);
+
+        let mut servers = Group::new("Servers");
+        servers.add_child(c);
+        let mut work = Group::new("Work");
+        work.add_child(b);
+        let servers_id = servers.uuid;
+        work.add_child(servers);
+        let mut personal = Group::new("Personal");
+        personal.add_child(d);
+
+        let mut root = Group::new("Root");
+        root.add_child(a);
+        let (work_id, personal_id) = (work.uuid, personal.uuid);
+        root.add_child(work);
+        root.add_child(personal);
+
+        Fixture {
+            root,
+            work: work_id,
+            servers: servers_id,
+            personal: personal_id,
+            a: a_id,
+            b: b_id,
+            c: c_id,
+            d: d_id,
+        }
+    }
+
+    fn title(root: &Group, uuid: &Uuid) -> Option<String> {
+        let path = find_entry(root, uuid)?;
+        entry_at(root, &path)?.get_title().map(str::to_string)
+    }
+
+    #[test]
+    fn finds_nested_entries_and_groups() {
+        let f = fixture();
+
+        assert_eq!(find_entry(&f.root, &f.a), Some(EntryPath { group: vec![], index: 0 }));
+        assert_eq!(find_entry(&f.root, &f.c), Some(EntryPath { group: vec![1, 1], index: 0 }));
+        assert_eq!(title(&f.root, &f.d).as_deref(), Some("d"));
+
+        assert_eq!(find_group(&f.root, &f.root.uuid), Some(vec![]));
+        assert_eq!(find_group(&f.root, &f.servers), Some(vec![1, 1]));
+        assert_eq!(group_at(&f.root, &[2]).map(|g| g.uuid), Some(f.personal));
+    }
+
+    #[test]
+    fn missing_nodes_and_bad_paths_are_none() {
+        let f = fixture();
+        let unknown = Uuid::new_v4();
+
+        assert_eq!(find_entry(&f.root, &unknown), None);
+        assert_eq!(find_group(&f.root, &unknown), None);
+        // Index 0 of the root is an entry, not a group
+        assert!(group_at(&f.root, &[0]).is_none());
+        assert!(group_at(&f.root, &[7]).is_none());
+        assert!(entry_at(&f.root, &EntryPath { group: vec![], index: 1 }).is_none());
+        // Entries are not groups, so an entry uuid never resolves as one
+        assert_eq!(find_group(&f.root, &f.a), None);
+    }
+
+    #[test]
+    fn mutable_lookups_change_the_tree() {
+        let mut f = fixture();
+
+        let path = find_entry(&f.root, &f.c).unwrap();
+        entry_at_mut(&mut f.root, &path).unwrap().fields.insert(
+            "Title".to_string(),
+            keepass::db::Value::Unprotected("changed".to_string()),
+        );
+        assert_eq!(title(&f.root, &f.c).as_deref(), Some("changed"));
+
+        let path = find_group(&f.root, &f.work).unwrap();
+        group_at_mut(&mut f.root, &path).unwrap().name = "Office".to_string();
+        assert_eq!(group_at(&f.root, &[1]).unwrap().name, "Office");
+    }
+
+    #[test]
+    fn remove_entry_detaches_only_that_entry() {
+        let mut f = fixture();
+
+        let removed = remove_entry(&mut f.root, &f.b).unwrap();
+        assert_eq!(removed.uuid, f.b);
+        assert_eq!(find_entry(&f.root, &f.b), None);
+
+        // The subgroup that followed it moved up one slot and is still reachable
+        assert_eq!(find_group(&f.root, &f.servers), Some(vec![1, 0]));
+        assert_eq!(title(&f.root, &f.c).as_deref(), Some("c"));
+        assert!(remove_entry(&mut f.root, &f.b).is_none());
+    }
+
+    #[test]
+    fn remove_group_takes_its_subtree() {
+        let mut f = fixture();
+
+        let removed = remove_group(&mut f.root, &f.work).unwrap();
+        assert_eq!(removed.uuid, f.work);
+        assert_eq!(find_group(&f.root, &f.servers), None);
+        assert_eq!(find_entry(&f.root, &f.c), None);
+        assert_eq!(find_group(&f.root, &f.personal), Some(vec![1]));
+
+        let root_uuid = f.root.uuid;
+        assert!(remove_group(&mut f.root, &root_uuid).is_none());
+    }
+
+    #[test]
+    fn moves_are_remove_then_add() {
+        let mut f = fixture();
+
+        let entry = remove_entry(&mut f.root, &f.a).unwrap();
+        let target = find_group(&f.root, &f.servers).unwrap();
+        group_at_mut(&mut f.root, &target).unwrap().add_child(entry);
+        assert_eq!(find_entry(&f.root, &f.a), Some(EntryPath { group: vec![0, 1], index: 1 }));
+
+        let group = remove_group(&mut f.root, &f.personal).unwrap();
+        let target = find_group(&f.root, &f.work).unwrap();
+        group_at_mut(&mut f.root, &target).unwrap().add_child(group);
+        assert_eq!(find_group(&f.root, &f.personal), Some(vec![0, 2]));
+        assert_eq!(title(&f.root, &f.d).as_deref(), Some("d"));
+    }
+
+    #[test]
+    fn is_within_detects_descendants() {
+        let f = fixture();
+        let work = find_group(&f.root, &f.work).unwrap();
+        let servers = find_group(&f.root, &f.servers).unwrap();
+        let personal = find_group(&f.root, &f.personal).unwrap();
+
+        assert!(is_within(&servers, &work));
+        assert!(is_within(&work, &work));
+        assert!(!is_within(&work, &servers));
+        assert!(!is_within(&personal, &work));
+    }
+}
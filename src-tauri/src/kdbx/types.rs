@@ -25,7 +25,8 @@ pub struct EntryData {
     pub password: String,
     pub url: String,
     pub notes: String,
-    pub tags: String,
+    /// Parsed from the KDBX `Tags` field, which separates them with `;` or `,`
+    pub tags: Vec<String>,
     pub group_uuid: String,
     pub icon_id: Option<usize>,
     pub is_favorite: bool,
@@ -39,6 +40,15 @@ pub struct EntryData {
     pub history: Vec<HistoryEntry>,
 }
 
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccessAction {
+    CopyPassword,
+    CopyUsername,
+    OpenUrl,
+    AutoType,
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct GroupData {
     pub uuid: String,
@@ -46,6 +56,10 @@ pub struct GroupData {
     pub parent_uuid: Option<String>,
     pub children: Vec<GroupData>,
     pub icon_id: Option<usize>,
+    /// The group's own `EnableSearching` / `EnableAutoType`; None inherits from the parent
+    pub enable_searching: Option<bool>,
+    pub enable_auto_type: Option<bool>,
+    pub is_recycle_bin: bool,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -57,6 +71,76 @@ pub struct KdfInfo {
     pub parallelism: Option<u32>,
 }
 
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KdfAlgorithm {
+    Aes,
+    Argon2d,
+    Argon2id,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "kdf_type", rename_all = "snake_case")]
+pub enum KdfSettings {
+    Aes { rounds: u64 },
+    Argon2d { iterations: u64, memory: u64, parallelism: u32 },
+    Argon2id { iterations: u64, memory: u64, parallelism: u32 },
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub enum OuterCipherSetting {
+    #[serde(rename = "aes256")]
+    Aes256,
+    #[serde(rename = "twofish")]
+    Twofish,
+    #[serde(rename = "chacha20")]
+    ChaCha20,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub enum InnerCipherSetting {
+    #[serde(rename = "plain")]
+    Plain,
+    #[serde(rename = "salsa20")]
+    Salsa20,
+    #[serde(rename = "chacha20")]
+    ChaCha20,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub enum CompressionSetting {
+    #[serde(rename = "none")]
+    None,
+    #[serde(rename = "gzip")]
+    Gzip,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DatabaseSettings {
+    pub outer_cipher: OuterCipherSetting,
+    pub inner_cipher: InnerCipherSetting,
+    pub compression: CompressionSetting,
+    pub kdbx_version: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DatabaseMetadata {
+    pub name: String,
+    pub description: String,
+    pub default_username: String,
+    pub color: Option<String>,
+    pub maintenance_history_days: Option<usize>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MasterKeyStatus {
+    pub last_changed: Option<String>,
+    pub recommend_after_days: Option<i64>,
+    pub force_after_days: Option<i64>,
+    pub change_recommended: bool,
+    pub change_forced: bool,
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct DashboardStats {
     pub total_entries: usize,
@@ -66,5 +150,119 @@ pub struct DashboardStats {
     pub old_passwords: usize,
     pub expired_entries: usize,
     pub favorite_entries: usize,
+    /// Mean of `PasswordQuality::entropy_bits` over all entries
     pub average_password_strength: f64,
 }
+
+/// How hard a password is to guess, judged by the patterns in it rather than its length alone
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PasswordQuality {
+    /// Estimated number of guesses an attacker needs
+    pub guesses: f64,
+    /// log2 of `guesses`, comparable to a plain entropy figure
+    pub entropy_bits: f64,
+    /// 0 (too guessable) to 4 (very unguessable)
+    pub score: u8,
+    pub warning: Option<String>,
+    pub suggestions: Vec<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BulkItemResult {
+    pub uuid: String,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BulkOperationResult {
+    pub applied: bool,
+    pub results: Vec<BulkItemResult>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct UndoState {
+    pub can_undo: bool,
+    pub can_redo: bool,
+    pub undo_label: Option<String>,
+    pub redo_label: Option<String>,
+    pub max_depth: usize,
+    pub keep_across_saves: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DatabaseStatus {
+    pub revision: u64,
+    pub dirty: bool,
+    pub read_only: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LockInfo {
+    pub id: String,
+    pub owner: String,
+    pub host: String,
+    pub pid: u32,
+    pub timestamp: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LockStatus {
+    pub locked: bool,
+    pub stale: bool,
+    pub owned_by_us: bool,
+    pub info: Option<LockInfo>,
+}
+
+/// How search terms are matched. All off means case-insensitive substring search
+/// over the unprotected fields.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchOptions {
+    pub regex: bool,
+    pub case_sensitive: bool,
+    /// Also match passwords and protected custom fields; only when the user opts in
+    pub include_protected: bool,
+    /// Also match older versions of entries, reported with a `history_index`
+    pub include_history: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TagCount {
+    pub name: String,
+    pub count: usize,
+}
+
+/// A query kept in the vault, shown like a virtual group
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SavedSearch {
+    pub uuid: String,
+    pub name: String,
+    pub query: String,
+    #[serde(default)]
+    pub options: SearchOptions,
+}
+
+/// Where a search term matched: a KDBX field name (`Title`, `UserName`, `URL`, `Notes`,
+/// `Tags`, `Password`, or a custom field's or attachment's name) and a byte range of its value.
+/// For `Tags` the range is within one tag, the one at `tag_index`.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SearchHighlight {
+    pub field: String,
+    /// The range is in the field's name instead, e.g. an attachment's file name
+    pub in_name: bool,
+    pub start: usize,
+    pub end: usize,
+    /// Index into `EntryData::tags` when the hit is in a tag
+    pub tag_index: Option<usize>,
+    /// Index into `EntryData::history` when the hit is in an older version of the entry
+    pub history_index: Option<usize>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SearchResult {
+    #[serde(flatten)]
+    pub entry: EntryData,
+    pub score: u32,
+    pub highlights: Vec<SearchHighlight>,
+}
@@ -0,0 +1,315 @@
+use keepass::db::{Group, Node};
+use std::collections::VecDeque;
+
+use super::database::Database;
+use super::error::DatabaseError;
+use super::index::TreeIndex;
+use super::types::UndoState;
+
+const DEFAULT_UNDO_DEPTH: usize = 50;
+
+struct Snapshot {
+    label: String,
+    root: Group,
+}
+
+/// Snapshot-based undo/redo journal. Each recorded step holds a copy of the
+/// group tree as it was before the mutation, so undo is a plain swap.
+pub struct UndoJournal {
+    undo_stack: VecDeque<Snapshot>,
+    redo_stack: Vec<Snapshot>,
+    max_depth: usize,
+    keep_across_saves: bool,
+    // Nesting depth of record_undo calls; only the outermost one records a step
+    depth: usize,
+}
+
+impl Default for UndoJournal {
+    fn default() -> Self {
+        Self {
+            undo_stack: VecDeque::new(),
+            redo_stack: Vec::new(),
+            max_depth: DEFAULT_UNDO_DEPTH,
+            keep_across_saves: false,
+            depth: 0,
+        }
+    }
+}
+
+impl UndoJournal {
+    fn push_undo(&mut self, snapshot: Snapshot) {
+        self.undo_stack.push_back(snapshot);
+        while self.undo_stack.len() > self.max_depth {
+            self.undo_stack.pop_front();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+    }
+
+    pub fn keep_across_saves(&self) -> bool {
+        self.keep_across_saves
+    }
+
+    pub fn state(&self) -> UndoState {
+        UndoState {
+            can_undo: !self.undo_stack.is_empty(),
+            can_redo: !self.redo_stack.is_empty(),
+            undo_label: self.undo_stack.back().map(|s| s.label.clone()),
+            redo_label: self.redo_stack.last().map(|s| s.label.clone()),
+            max_depth: self.max_depth,
+            keep_across_saves: self.keep_across_saves,
+        }
+    }
+}
+
+impl Database {
+    /// Runs `f` as a single undoable step and marks the database dirty on success.
+    /// If `f` fails, the tree is put back the way it was. Nested calls (e.g. bulk
+    /// operations that call the single-entry methods) are folded into the outermost step.
+    pub(super) fn record_undo<T, F>(&mut self, label: &str, f: F) -> Result<T, DatabaseError>
+    where
+        F: FnOnce(&mut Self) -> Result<T, DatabaseError>,
+    {
+        // Every mutation passes through here, so this is where read-only mode is enforced
+        self.ensure_writable()?;
+
+        if self.undo.depth > 0 {
+            self.undo.depth += 1;
+            let result = f(self);
+            self.undo.depth -= 1;
+            return result;
+        }
+
+        let before = self.db.root.clone();
+        self.undo.depth += 1;
+        let result = f(self);
+        self.undo.depth -= 1;
+
+        if result.is_err() {
+            // The step may have changed the tree before bailing out. The index still
+            // fits `before`: nested steps don't rebuild it.
+            self.db.root = before;
+            return result;
+        }
+
+        if !TreeIndex::same_shape(&before, &self.db.root) {
+            self.reindex();
+        }
+        self.mark_dirty();
+        if self.undo.max_depth > 0 {
+            self.undo.push_undo(Snapshot {
+                label: label.to_string(),
+                root: before,
+            });
+            // A new change invalidates everything that could have been redone
+            self.undo.redo_stack.clear();
+        }
+
+        result
+    }
+
+    pub fn undo(&mut self) -> Result<UndoState, DatabaseError> {
+        self.ensure_writable()?;
+        let snapshot = self.undo.undo_stack.pop_back().ok_or(DatabaseError::NothingToUndo)?;
+        let current = std::mem::replace(&mut self.db.root, snapshot.root);
+        if !TreeIndex::same_shape(&current, &self.db.root) {
+            self.reindex();
+        }
+        self.carry_usage_stats(&current);
+        self.undo.redo_stack.push(Snapshot {
+            label: snapshot.label,
+            root: current,
+        });
+        self.mark_dirty();
+        Ok(self.undo.state())
+    }
+
+    pub fn redo(&mut self) -> Result<UndoState, DatabaseError> {
+        self.ensure_writable()?;
+        let snapshot = self.undo.redo_stack.pop().ok_or(DatabaseError::NothingToRedo)?;
+        let current = std::mem::replace(&mut self.db.root, snapshot.root);
+        if !TreeIndex::same_shape(&current, &self.db.root) {
+            self.reindex();
+        }
+        self.carry_usage_stats(&current);
+        self.undo.push_undo(Snapshot {
+            label: snapshot.label,
+            root: current,
+        });
+        self.mark_dirty();
+        Ok(self.undo.state())
+    }
+
+    /// Usage counts and access times aren't edits (see `record_entry_access`), so they
+    /// aren't journaled either: the restored tree keeps the ones from the tree it replaces.
+    fn carry_usage_stats(&mut self, replaced: &Group) {
+        for node in &replaced.children {
+            match node {
+                Node::Entry(old) => {
+                    if let Some(entry) = self.index.entry_mut(&mut self.db.root, &old.uuid) {
+                        entry.times.usage_count = old.times.usage_count;
+                        if let Some(last_access) = old.times.get_last_access() {
+                            entry.times.set_last_access(*last_access);
+                        }
+                    }
+                }
+                Node::Group(group) => self.carry_usage_stats(group),
+            }
+        }
+    }
+
+    pub fn get_undo_state(&self) -> UndoState {
+        self.undo.state()
+    }
+
+    pub fn set_undo_settings(&mut self, max_depth: usize, keep_across_saves: bool) -> UndoState {
+        self.undo.max_depth = max_depth;
+        self.undo.keep_across_saves = keep_across_saves;
+        while self.undo.undo_stack.len() > max_depth {
+            self.undo.undo_stack.pop_front();
+        }
+        if max_depth == 0 {
+            self.undo.redo_stack.clear();
+        }
+        self.undo.state()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kdbx::types::AccessAction;
+    use keepass::config::DatabaseConfig;
+    use keepass::db::{Entry, Value};
+    use keepass::Database as KeepassDatabase;
+
+    fn fixture() -> (Database, String) {
+        let mut db = KeepassDatabase::new(DatabaseConfig::default());
+        let mut entry = Entry::new();
+        entry.fields.insert("Title".to_string(), Value::Unprotected("v0".to_string()));
+        let uuid = entry.uuid.to_string();
+        db.root.add_child(entry);
+        (Database::in_memory(db), uuid)
+    }
+
+    fn set_title(db: &mut Database, uuid: &str, title: &str) {
+        db.record_undo(title, |db| {
+            let entry = db.find_entry_by_uuid_mut(uuid)?;
+            entry.fields.insert("Title".to_string(), Value::Unprotected(title.to_string()));
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    fn title(db: &Database, uuid: &str) -> String {
+        db.find_entry_by_uuid(uuid).unwrap().get_title().unwrap_or("").to_string()
+    }
+
+    #[test]
+    fn undo_and_redo_walk_the_steps() {
+        let (mut db, uuid) = fixture();
+        set_title(&mut db, &uuid, "v1");
+        set_title(&mut db, &uuid, "v2");
+
+        let state = db.undo().unwrap();
+        assert_eq!(title(&db, &uuid), "v1");
+        assert_eq!(state.undo_label.as_deref(), Some("v1"));
+        assert_eq!(state.redo_label.as_deref(), Some("v2"));
+
+        db.undo().unwrap();
+        assert_eq!(title(&db, &uuid), "v0");
+        assert!(matches!(db.undo(), Err(DatabaseError::NothingToUndo)));
+
+        db.redo().unwrap();
+        assert_eq!(title(&db, &uuid), "v1");
+
+        // A new edit drops what could have been redone
+        set_title(&mut db, &uuid, "v3");
+        assert!(matches!(db.redo(), Err(DatabaseError::NothingToRedo)));
+        db.undo().unwrap();
+        assert_eq!(title(&db, &uuid), "v1");
+    }
+
+    #[test]
+    fn nested_steps_fold_into_the_outer_one() {
+        let (mut db, uuid) = fixture();
+
+        db.record_undo("Outer", |db| {
+            set_title(db, &uuid, "v1");
+            set_title(db, &uuid, "v2");
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(db.get_undo_state().undo_label.as_deref(), Some("Outer"));
+        db.undo().unwrap();
+        assert_eq!(title(&db, &uuid), "v0");
+        assert!(!db.get_undo_state().can_undo);
+    }
+
+    #[test]
+    fn the_oldest_steps_are_evicted_past_the_depth() {
+        let (mut db, uuid) = fixture();
+        db.set_undo_settings(2, false);
+        for title in ["v1", "v2", "v3"] {
+            set_title(&mut db, &uuid, title);
+        }
+
+        db.undo().unwrap();
+        db.undo().unwrap();
+        assert_eq!(title(&db, &uuid), "v1");
+        assert!(!db.get_undo_state().can_undo);
+
+        // Lowering the depth trims the journal right away, and zero turns it off
+        let state = db.set_undo_settings(0, false);
+        assert!(!state.can_undo && !state.can_redo);
+        set_title(&mut db, &uuid, "v4");
+        assert!(!db.get_undo_state().can_undo);
+    }
+
+    #[test]
+    fn saving_clears_the_journal_unless_asked_to_keep_it() {
+        let (mut db, uuid) = fixture();
+        set_title(&mut db, &uuid, "v1");
+        let pending = db.prepare_save().unwrap();
+        db.finish_save(pending);
+        assert!(!db.get_undo_state().can_undo);
+
+        db.set_undo_settings(50, true);
+        set_title(&mut db, &uuid, "v2");
+        let pending = db.prepare_save().unwrap();
+        db.finish_save(pending);
+        assert!(db.get_undo_state().can_undo);
+
+        // An edit made while the file was being written isn't in it, so it stays undoable
+        db.set_undo_settings(50, false);
+        let pending = db.prepare_save().unwrap();
+        set_title(&mut db, &uuid, "v3");
+        db.finish_save(pending);
+        assert_eq!(db.get_undo_state().undo_label.as_deref(), Some("v3"));
+    }
+
+    #[test]
+    fn usage_stats_survive_undo_and_redo() {
+        let (mut db, uuid) = fixture();
+        set_title(&mut db, &uuid, "v1");
+        db.record_entry_access(&uuid, AccessAction::CopyPassword).unwrap();
+        db.record_entry_access(&uuid, AccessAction::OpenUrl).unwrap();
+        let last_access = *db.find_entry_by_uuid(&uuid).unwrap().times.get_last_access().unwrap();
+
+        db.undo().unwrap();
+        let entry = db.find_entry_by_uuid(&uuid).unwrap();
+        assert_eq!(entry.get_title(), Some("v0"));
+        assert_eq!(entry.times.usage_count, 2);
+        assert_eq!(entry.times.get_last_access(), Some(&last_access));
+
+        db.record_entry_access(&uuid, AccessAction::CopyUsername).unwrap();
+        db.redo().unwrap();
+        let entry = db.find_entry_by_uuid(&uuid).unwrap();
+        assert_eq!(entry.get_title(), Some("v1"));
+        assert_eq!(entry.times.usage_count, 3);
+    }
+}
@@ -0,0 +1,24 @@
+use crate::kdbx::{LockFile, LockRefresher};
+use crate::state::AppState;
+use tauri::{AppHandle, Manager};
+
+// Lock files are otherwise only refreshed by saves, so a vault left open overnight
+// without changes would look abandoned to other instances and be taken over
+pub fn start(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(LockFile::REFRESH_INTERVAL);
+
+        let state = app.state::<AppState>();
+        let refreshers: Vec<LockRefresher> = match state.databases.read() {
+            Ok(databases) => databases.values().filter_map(|db| db.lock_refresher()).collect(),
+            Err(e) => {
+                eprintln!("lock_refresh: Lock poisoned: {}", e);
+                continue;
+            }
+        };
+        // The file I/O runs with the database lock released, so commands never wait on it
+        for refresher in refreshers {
+            refresher.refresh();
+        }
+    });
+}
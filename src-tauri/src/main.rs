@@ -1,14 +1,19 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod autosave;
 mod kdbx;
 mod commands;
+mod events;
+mod lock_refresh;
 mod state;
+mod watcher;
 
 use state::AppState;
-use std::sync::Mutex;
+use std::sync::{Mutex, RwLock};
+use std::sync::atomic::{AtomicBool, AtomicU64};
 use std::collections::HashMap;
-use tauri::Manager;
+use tauri::{Manager, WindowEvent};
 use tauri::tray::{TrayIconBuilder, TrayIconEvent, MouseButton};
 use tauri::menu::{Menu, MenuItem};
 
@@ -19,22 +24,57 @@ fn main() {
         .plugin(tauri_plugin_clipboard_manager::init())
         .plugin(tauri_plugin_shell::init())
         .manage(AppState {
-            database: Mutex::new(None),
+            databases: RwLock::new(HashMap::new()),
             initial_file_path: Mutex::new(None),
             dismissed_breaches: Mutex::new(HashMap::new()),
+            autosave: Mutex::new(autosave::AutosaveSettings::default()),
+            autosave_generation: AtomicU64::new(0),
+            save_lock: Mutex::new(()),
+            watchers: Mutex::new(HashMap::new()),
+            auto_merge_external_changes: AtomicBool::new(false),
+        })
+        .on_window_event(|window, event| {
+            // Flush pending changes before the main window goes away
+            if let WindowEvent::CloseRequested { .. } = event {
+                if window.label() == "main" {
+                    let _ = autosave::save_before_close(window.app_handle(), None);
+                }
+            }
         })
         .invoke_handler(tauri::generate_handler![
             commands::database::get_initial_file_path,
             commands::database::clear_initial_file_path,
+            commands::database::list_open_databases,
             commands::database::create_database,
             commands::database::open_database,
             commands::database::save_database,
+            commands::database::save_database_as,
+            commands::database::save_database_copy,
             commands::database::close_database,
             commands::database::get_kdf_info,
             commands::database::upgrade_kdf_parameters,
+            commands::database::set_kdf_settings,
+            commands::database::benchmark_kdf,
             commands::database::check_database_changes,
             commands::database::merge_database,
             commands::database::get_groups,
+            commands::database::undo,
+            commands::database::redo,
+            commands::database::get_undo_state,
+            commands::database::set_undo_settings,
+            commands::database::get_database_status,
+            commands::database::get_autosave_settings,
+            commands::database::set_autosave_settings,
+            commands::database::get_database_lock_status,
+            commands::database::break_database_lock,
+            commands::database::set_auto_merge_external_changes,
+            commands::database::get_database_settings,
+            commands::database::set_database_settings,
+            commands::database::get_database_metadata,
+            commands::database::set_database_metadata,
+            commands::database::get_master_key_status,
+            commands::database::set_master_key_policy,
+            commands::database::change_master_key,
             commands::entry::get_entries,
             commands::entry::get_favorite_entries,
             commands::entry::get_entry,
@@ -42,15 +82,39 @@ fn main() {
             commands::entry::update_entry,
             commands::entry::delete_entry,
             commands::entry::move_entry,
+            commands::entry::record_entry_access,
+            commands::entry::get_most_used_entries,
+            commands::entry::get_recently_used_entries,
+            commands::bulk::move_entries,
+            commands::bulk::delete_entries,
+            commands::bulk::add_tags_to_entries,
+            commands::bulk::remove_tags_from_entries,
+            commands::tags::get_all_tags,
+            commands::tags::get_entries_with_tags,
+            commands::tags::rename_tag,
+            commands::tags::merge_tags,
+            commands::tags::delete_tag,
+            commands::bulk::set_entries_favorite,
+            commands::bulk::set_entries_expiry,
+            commands::bulk::set_entries_icon,
             commands::group::create_group,
             commands::group::rename_group,
             commands::group::move_group,
             commands::group::reorder_group,
             commands::group::delete_group,
+            commands::group::set_group_searching,
+            commands::group::set_group_auto_type,
             commands::search::search_entries,
             commands::search::search_entries_in_group,
+            commands::search::search_all_databases,
             commands::password::generate_password,
+            commands::password::password_quality,
             commands::search::get_dashboard_stats,
+            commands::search::get_saved_searches,
+            commands::search::create_saved_search,
+            commands::search::update_saved_search,
+            commands::search::delete_saved_search,
+            commands::search::run_saved_search,
             commands::security::check_breached_passwords,
             commands::security::save_dismissed_breach,
             commands::security::get_dismissed_breaches,
@@ -91,6 +155,8 @@ fn main() {
                 })
                 .build(app)?;
 
+            lock_refresh::start(app.handle().clone());
+
             // Handle file associations - check if app was opened with a .kdbx file
             let args: Vec<String> = std::env::args().collect();
             if args.len() > 1 {
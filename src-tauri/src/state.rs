@@ -1,9 +1,20 @@
+use crate::autosave::AutosaveSettings;
 use crate::kdbx::Database;
-use std::sync::Mutex;
+use crate::watcher::DatabaseWatcher;
+use std::sync::{Mutex, RwLock};
+use std::sync::atomic::{AtomicBool, AtomicU64};
 use std::collections::{HashMap, HashSet};
 
 pub struct AppState {
-    pub database: Mutex<Option<Database>>,
+    // Open vaults keyed by the id handed out by create_database/open_database.
+    // Queries share a read lock; only mutations and the end of a save take the write lock.
+    pub databases: RwLock<HashMap<String, Database>>,
     pub initial_file_path: Mutex<Option<String>>,
     pub dismissed_breaches: Mutex<HashMap<String, HashSet<String>>>,
+    pub autosave: Mutex<AutosaveSettings>,
+    pub autosave_generation: AtomicU64,
+    // Held for the duration of every write to the database file
+    pub save_lock: Mutex<()>,
+    pub watchers: Mutex<HashMap<String, DatabaseWatcher>>,
+    pub auto_merge_external_changes: AtomicBool,
 }
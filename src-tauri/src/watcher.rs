@@ -0,0 +1,144 @@
+use crate::events::{emit_db_changed, ChangeKind};
+use crate::kdbx::Database;
+use crate::state::AppState;
+use notify_debouncer_mini::notify::{RecommendedWatcher, RecursiveMode};
+use notify_debouncer_mini::{new_debouncer, DebounceEventResult, Debouncer};
+use secrecy::{ExposeSecret, SecretString};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+pub const EXTERNAL_CHANGE_EVENT: &str = "database-changed-externally";
+
+// Cloud sync clients and editors often write a file in several bursts
+const DEBOUNCE: Duration = Duration::from_millis(750);
+
+#[derive(Clone, Serialize)]
+struct ExternalChange {
+    database_id: String,
+    path: String,
+    merged: bool,
+}
+
+/// Keeps the OS watcher alive; dropping it stops watching.
+pub struct DatabaseWatcher {
+    _debouncer: Debouncer<RecommendedWatcher>,
+}
+
+// Watchers report resolved paths, while the vault may have been opened through a
+// symlink or a relative path
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+pub fn start(app: &AppHandle, database_id: &str, db_path: &Path) -> Result<DatabaseWatcher, String> {
+    let path = db_path.to_path_buf();
+    let target = canonical(db_path);
+    let database_id = database_id.to_string();
+    // Watch the directory rather than the file: many tools save by writing a
+    // temp file and renaming it over the original, which replaces the inode
+    let dir = target
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("."));
+
+    let app_handle = app.clone();
+    let mut debouncer = new_debouncer(DEBOUNCE, move |result: DebounceEventResult| {
+        match result {
+            Ok(events) => {
+                if events.iter().any(|event| canonical(&event.path) == target) {
+                    handle_change(&app_handle, &database_id, &path);
+                }
+            }
+            Err(e) => eprintln!("watcher: Watch error: {}", e),
+        }
+    })
+    .map_err(|e| format!("Failed to create file watcher: {}", e))?;
+
+    debouncer
+        .watcher()
+        .watch(&dir, RecursiveMode::NonRecursive)
+        .map_err(|e| format!("Failed to watch {}: {}", dir.display(), e))?;
+
+    Ok(DatabaseWatcher { _debouncer: debouncer })
+}
+
+fn handle_change(app: &AppHandle, database_id: &str, path: &Path) {
+    let state = app.state::<AppState>();
+    let auto_merge = state.auto_merge_external_changes.load(Ordering::SeqCst);
+
+    // Our own saves write the file without holding the database lock; waiting for
+    // them keeps a half-finished save from looking like an external change
+    let _save_guard = match state.save_lock.lock() {
+        Ok(guard) => guard,
+        Err(e) => {
+            eprintln!("watcher: Save lock poisoned: {}", e);
+            return;
+        }
+    };
+
+    let merge_source = {
+        let databases = match state.databases.read() {
+            Ok(lock) => lock,
+            Err(e) => {
+                eprintln!("watcher: Lock poisoned: {}", e);
+                return;
+            }
+        };
+        let Some(db) = databases.get(database_id) else {
+            return;
+        };
+        // The watcher may outlive a Save As to another file by a few milliseconds
+        if canonical(&db.path) != canonical(path) {
+            return;
+        }
+
+        // Content hash comparison filters out touch-only changes and our own saves
+        if !db.has_external_changes() {
+            return;
+        }
+
+        (auto_merge && !db.is_dirty() && !db.is_read_only())
+            .then(|| SecretString::new(db.password.expose_secret().clone()))
+    };
+
+    let merged = match merge_source {
+        Some(password) => merge(app, &state, database_id, path, &password)
+            .map_err(|e| eprintln!("watcher: Auto-merge failed: {}", e))
+            .is_ok(),
+        None => false,
+    };
+
+    let payload = ExternalChange {
+        database_id: database_id.to_string(),
+        path: path.to_string_lossy().to_string(),
+        merged,
+    };
+    if let Err(e) = app.emit(EXTERNAL_CHANGE_EVENT, payload) {
+        eprintln!("watcher: Failed to emit event: {}", e);
+    }
+}
+
+// Decrypts the new file without holding the database lock, then takes it over. Only
+// used without local changes, so there is nothing to merge: a union merge would bring
+// back entries the other writer deleted and leave them to be saved again.
+fn merge(
+    app: &AppHandle,
+    state: &AppState,
+    database_id: &str,
+    path: &Path,
+    password: &SecretString,
+) -> Result<(), String> {
+    let disk_copy = Database::read_disk_copy(path, password).map_err(|e| e.to_string())?;
+
+    let mut databases = state.databases.write().map_err(|e| e.to_string())?;
+    let db = databases
+        .get_mut(database_id)
+        .ok_or("Database was closed".to_string())?;
+    // Fails if something was edited while the file was being decrypted
+    db.reload_disk_copy(disk_copy).map_err(|e| e.to_string())?;
+    emit_db_changed(app, database_id, db, ChangeKind::Merged, Vec::new());
+    Ok(())
+}